@@ -1,85 +1,91 @@
-const GRID_SIZE: usize = 10;
+use utils::{
+    automaton::{Automaton, Rule},
+    Grid,
+};
 
-fn parse_input() -> Vec<Vec<u8>> {
-    include_str!("../input.txt")
-        .lines()
-        .map(|l| l.bytes().map(|b| b - b'0').collect())
-        .collect()
-}
-
-fn neighbors(i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
-    [
-        (i.wrapping_sub(1), j.wrapping_sub(1)),
-        (i.wrapping_sub(1), j),
-        (i.wrapping_sub(1), j + 1),
-        (i, j.wrapping_sub(1)),
-        (i, j + 1),
-        (i + 1, j.wrapping_sub(1)),
-        (i + 1, j),
-        (i + 1, j + 1),
-    ]
-    .into_iter()
-    .filter(|&(i, j)| i < GRID_SIZE && j < GRID_SIZE)
-}
+/// Energy levels rise by 1 at each step, octopuses above 9 flash and raise
+/// their neighbours, then go back to 0.
+struct Octopuses;
 
-fn flash(grid: &mut [Vec<u8>], i: usize, j: usize) -> usize {
-    let mut count = 1;
+impl Rule for Octopuses {
+    type Cell = u8;
 
-    grid[i][j] = 0;
-    for (ni, nj) in neighbors(i, j) {
-        if grid[ni][nj] > 0 {
-            grid[ni][nj] += 1;
-            if grid[ni][nj] >= 10 {
-                count += flash(grid, ni, nj);
-            }
-        }
+    fn update(&self, grid: &Grid<u8>, row: i32, col: i32) -> u8 {
+        grid.get(row, col).unwrap() + 1
     }
 
-    count
-}
-
-fn octopuses_step(grid: &mut [Vec<u8>]) -> usize {
-    let mut count = 0;
+    fn fires(&self, energy: &u8) -> bool {
+        *energy > 9
+    }
 
-    grid.iter_mut().flatten().for_each(|p| *p += 1);
+    fn propagate(&self, energy: &mut u8) {
+        *energy += 1;
+    }
 
-    for i in 0..GRID_SIZE {
-        for j in 0..GRID_SIZE {
-            if grid[i][j] >= 10 {
-                count += flash(grid, i, j);
-            }
-        }
+    fn settle(&self, energy: &mut u8) {
+        *energy = 0;
     }
+}
+
+fn parse_input(input: &str) -> Automaton<Octopuses> {
+    let mut grid = Grid::<u8>::parse(input);
+    grid.grid.iter_mut().for_each(|b| *b -= b'0');
 
-    count
+    Automaton::new(Octopuses, grid)
 }
 
-fn part1() -> usize {
-    let mut grid = parse_input();
-    (0..100).map(|_| octopuses_step(&mut grid)).sum()
+fn part1(input: &str) -> usize {
+    let mut octopuses = parse_input(input);
+    octopuses.run(100).iter().map(|stats| stats.fired).sum()
 }
 
-fn part2() -> usize {
-    let mut grid = parse_input();
-    (0..)
-        .find(|_| octopuses_step(&mut grid) == GRID_SIZE * GRID_SIZE)
-        .map(|step| step + 1)
+fn part2(input: &str) -> usize {
+    let mut octopuses = parse_input(input);
+    octopuses
+        .run_until(usize::MAX, |stats, grid| stats.fired == grid.grid.len())
         .expect("Couldn't find step")
+        .step
 }
 
-fn main() {}
+fn main() {
+    println!("part1={}", part1(include_str!("../input.txt")));
+    println!("part2={}", part2(include_str!("../input.txt")));
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    const TEST_INPUT: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1());
+        assert_eq!(part1(TEST_INPUT), 1656);
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2());
+        assert_eq!(part2(TEST_INPUT), 195);
+    }
+
+    #[test]
+    fn test_any_size() {
+        // the 5x5 example from the puzzle: 9 flashes, then none
+        let mut octopuses = parse_input("11111\n19991\n19191\n19991\n11111");
+        let stats = octopuses.run(2);
+        assert_eq!(stats[0].fired, 9);
+        assert_eq!(stats[1].fired, 0);
+        assert_eq!(
+            octopuses.grid().to_string(),
+            "45654\n51115\n61116\n51115\n45654\n"
+        );
     }
 }
@@ -0,0 +1,270 @@
+use std::{collections::HashMap, fmt, hash::Hash};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidLine(String),
+    MissingCave(&'static str),
+    /// Paths could go back and forth between them forever
+    LinkedBigCaves(String, String),
+    TooManySmallCaves,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidLine(line) => write!(f, "invalid line {line:?}"),
+            ParseError::MissingCave(name) => write!(f, "no {name} cave"),
+            ParseError::LinkedBigCaves(a, b) => write!(f, "big caves {a} and {b} are linked"),
+            ParseError::TooManySmallCaves => write!(f, "more than 64 small caves"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A cave about to be entered, as seen by a visit policy.
+#[derive(Clone, Copy, Debug)]
+pub struct Cave {
+    pub id: usize,
+    pub small: bool,
+    /// Whether the path already went through it, only tracked for small caves
+    pub visited: bool,
+}
+
+/// Which caves a path may enter. Big caves can always be entered, unless the
+/// policy says otherwise. The start and end caves are handled by the graph.
+pub trait VisitPolicy {
+    /// What the policy remembers of the path so far.
+    type State: Clone + Eq + Hash;
+
+    fn initial(&self, caves: &Caves) -> Self::State;
+
+    /// The state after entering `cave`, or `None` if it can't be entered.
+    fn enter(&self, state: &Self::State, cave: Cave) -> Option<Self::State>;
+}
+
+/// Small caves at most once.
+pub struct Once;
+
+impl VisitPolicy for Once {
+    type State = ();
+
+    fn initial(&self, _caves: &Caves) {}
+
+    fn enter(&self, _state: &(), cave: Cave) -> Option<()> {
+        (!cave.visited).then_some(())
+    }
+}
+
+/// Small caves at most once, except for a single one visited twice.
+pub struct OneTwice;
+
+impl VisitPolicy for OneTwice {
+    /// Whether a small cave was visited twice already
+    type State = bool;
+
+    fn initial(&self, _caves: &Caves) -> bool {
+        false
+    }
+
+    fn enter(&self, &twice: &bool, cave: Cave) -> Option<bool> {
+        match (cave.visited, twice) {
+            (false, _) => Some(twice),
+            (true, false) => Some(true),
+            (true, true) => None,
+        }
+    }
+}
+
+/// Each small cave at most `k` times.
+pub struct AtMost(pub u8);
+
+impl VisitPolicy for AtMost {
+    /// Visits of each cave
+    type State = Vec<u8>;
+
+    fn initial(&self, caves: &Caves) -> Vec<u8> {
+        vec![0; caves.names.len()]
+    }
+
+    fn enter(&self, visits: &Vec<u8>, cave: Cave) -> Option<Vec<u8>> {
+        if !cave.small {
+            return Some(visits.clone());
+        }
+        (visits[cave.id] < self.0).then(|| {
+            let mut visits = visits.clone();
+            visits[cave.id] += 1;
+            visits
+        })
+    }
+}
+
+/// The cave system, with caves interned as indices into `names`.
+#[derive(Debug)]
+pub struct Caves {
+    names: Vec<String>,
+    /// Bit of each small cave in the visited masks
+    small: Vec<Option<u32>>,
+    adjacency: Vec<Vec<usize>>,
+    start: usize,
+    end: usize,
+}
+
+impl Caves {
+    /// `a-b` lines, one per tunnel.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut ids = HashMap::new();
+        let mut names = Vec::new();
+        let mut adjacency: Vec<Vec<usize>> = Vec::new();
+        let mut intern = |name: &str| {
+            *ids.entry(name.to_owned()).or_insert_with(|| {
+                names.push(name.to_owned());
+                adjacency.push(Vec::new());
+                names.len() - 1
+            })
+        };
+
+        let mut tunnels = Vec::new();
+        for line in input.lines() {
+            let (a, b) = line
+                .split_once('-')
+                .filter(|(a, b)| !a.is_empty() && !b.is_empty())
+                .ok_or_else(|| ParseError::InvalidLine(line.to_owned()))?;
+            tunnels.push((intern(a), intern(b)));
+        }
+        for (a, b) in tunnels {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+
+        let is_big = |id: usize| names[id].chars().all(|c| c.is_uppercase());
+        for (a, next) in adjacency.iter().enumerate() {
+            if let Some(&b) = next.iter().find(|&&b| is_big(a) && is_big(b)) {
+                return Err(ParseError::LinkedBigCaves(
+                    names[a].clone(),
+                    names[b].clone(),
+                ));
+            }
+        }
+
+        let mut small = vec![None; names.len()];
+        let mut bits = 0;
+        for (id, bit) in small.iter_mut().enumerate() {
+            if !is_big(id) {
+                *bit = Some(bits);
+                bits += 1;
+            }
+        }
+        if bits > 64 {
+            return Err(ParseError::TooManySmallCaves);
+        }
+
+        let start = *ids.get("start").ok_or(ParseError::MissingCave("start"))?;
+        let end = *ids.get("end").ok_or(ParseError::MissingCave("end"))?;
+
+        Ok(Caves {
+            names,
+            small,
+            adjacency,
+            start,
+            end,
+        })
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    /// Caves the path can go to from `from`, with the state and visited mask
+    /// after entering them.
+    fn next<'a, P: VisitPolicy>(
+        &'a self,
+        policy: &'a P,
+        from: usize,
+        mask: u64,
+        state: &'a P::State,
+    ) -> impl Iterator<Item = (usize, u64, P::State)> + 'a {
+        self.adjacency[from]
+            .iter()
+            .filter(move |&&id| id != self.start)
+            .filter_map(move |&id| {
+                let bit = self.small[id].map_or(0, |bit| 1 << bit);
+                let cave = Cave {
+                    id,
+                    small: bit != 0,
+                    visited: mask & bit != 0,
+                };
+                let state = policy.enter(state, cave)?;
+                Some((id, mask | bit, state))
+            })
+    }
+
+    /// Number of paths from start to end, memoised on the current cave, the
+    /// small caves visited and the policy state.
+    pub fn count(&self, policy: &impl VisitPolicy) -> u64 {
+        fn count<P: VisitPolicy>(
+            caves: &Caves,
+            policy: &P,
+            cave: usize,
+            mask: u64,
+            state: P::State,
+            memo: &mut HashMap<(usize, u64, P::State), u64>,
+        ) -> u64 {
+            if cave == caves.end {
+                return 1;
+            }
+            let key = (cave, mask, state);
+            if let Some(&n) = memo.get(&key) {
+                return n;
+            }
+
+            let n = caves
+                .next(policy, cave, mask, &key.2)
+                .map(|(next, mask, state)| count(caves, policy, next, mask, state, memo))
+                .sum();
+            memo.insert(key, n);
+            n
+        }
+
+        let mask = self.small[self.start].map_or(0, |bit| 1 << bit);
+        let state = policy.initial(self);
+        count(self, policy, self.start, mask, state, &mut HashMap::new())
+    }
+
+    /// Every path from start to end, as cave names.
+    pub fn paths(&self, policy: &impl VisitPolicy) -> Vec<Vec<&str>> {
+        fn walk<'a, P: VisitPolicy>(
+            caves: &'a Caves,
+            policy: &P,
+            mask: u64,
+            state: &P::State,
+            path: &mut Vec<usize>,
+            paths: &mut Vec<Vec<&'a str>>,
+        ) {
+            let cave = *path.last().unwrap();
+            if cave == caves.end {
+                paths.push(path.iter().map(|&id| caves.name(id)).collect());
+                return;
+            }
+
+            for (next, mask, state) in caves.next(policy, cave, mask, state) {
+                path.push(next);
+                walk(caves, policy, mask, &state, path, paths);
+                path.pop();
+            }
+        }
+
+        let mask = self.small[self.start].map_or(0, |bit| 1 << bit);
+        let mut paths = Vec::new();
+        walk(
+            self,
+            policy,
+            mask,
+            &policy.initial(self),
+            &mut vec![self.start],
+            &mut paths,
+        );
+
+        paths
+    }
+}
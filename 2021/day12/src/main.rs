@@ -1,86 +1,119 @@
-use std::collections::HashMap;
+mod caves;
 
-struct Edge {
-    start: String,
-    end: String,
-}
+use caves::{AtMost, Caves, Once, OneTwice};
 
-fn is_big_cave(cave: &str) -> bool {
-    cave.chars().any(|c| c.is_uppercase())
-}
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn is_small_cave(cave: &str) -> bool {
-    cave.chars().any(|c| c.is_lowercase())
+fn part1(input: &str) -> Result<u64> {
+    Ok(Caves::parse(input)?.count(&Once))
 }
 
-fn parse_input() -> Vec<Edge> {
-    include_str!("../input.txt")
-        .lines()
-        .flat_map(|l| {
-            let (s1, s2) = l.split_once('-').unwrap();
-            [
-                Edge {
-                    start: s1.to_owned(),
-                    end: s2.to_owned(),
-                },
-                Edge {
-                    start: s2.to_owned(),
-                    end: s1.to_owned(),
-                },
-            ]
-            .into_iter()
-        })
-        .collect::<Vec<Edge>>()
+fn part2(input: &str) -> Result<u64> {
+    Ok(Caves::parse(input)?.count(&OneTwice))
 }
 
-fn dfs(edges: &[Edge], start: &str, visited: &mut HashMap<String, u32>, max_visits: u32) -> u32 {
-    edges
-        .iter()
-        .filter(|edge| edge.start == start)
-        .map(|edge| match &edge.end[..] {
-            "start" => 0,
-            "end" => 1,
-            _ => {
-                let mut sum = 0;
-                if is_big_cave(&edge.end) || *visited.get(&edge.end).unwrap_or(&0) < max_visits {
-                    let counter = visited.entry(edge.end.to_owned()).or_insert(0);
-                    *counter += 1;
-                    if is_small_cave(&edge.end) && *counter == max_visits {
-                        sum = dfs(edges, &edge.end, visited, 1);
-                    } else {
-                        sum = dfs(edges, &edge.end, visited, max_visits);
-                    }
-                    *visited.get_mut(&edge.end).unwrap() -= 1;
-                }
-                sum
+fn main() -> Result<()> {
+    // cargo run -- --paths 1
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, visits] = &args[..] {
+        if flag == "--paths" {
+            let caves = Caves::parse(include_str!("../input.txt"))?;
+            for path in caves.paths(&AtMost(visits.parse()?)) {
+                println!("{}", path.join(","));
             }
-        })
-        .sum()
-}
-
-fn part1() -> u32 {
-    let edges = parse_input();
-    dfs(&edges, "start", &mut HashMap::new(), 1)
-}
+            return Ok(());
+        }
+    }
 
-fn part2() -> u32 {
-    let edges = parse_input();
-    dfs(&edges, "start", &mut HashMap::new(), 2)
+    println!("part1={}", part1(include_str!("../input.txt"))?);
+    println!("part2={}", part2(include_str!("../input.txt"))?);
+    Ok(())
 }
 
-fn main() {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    const SMALL: &str = "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
+    const LARGER: &str = "dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc";
+    const LARGEST: &str = "fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1());
+        assert_eq!(part1(SMALL).unwrap(), 10);
+        assert_eq!(part1(LARGER).unwrap(), 19);
+        assert_eq!(part1(LARGEST).unwrap(), 226);
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2());
+        assert_eq!(part2(SMALL).unwrap(), 36);
+        assert_eq!(part2(LARGER).unwrap(), 103);
+        assert_eq!(part2(LARGEST).unwrap(), 3509);
+    }
+
+    #[test]
+    fn test_paths() {
+        let caves = Caves::parse(SMALL).unwrap();
+        let mut paths = caves.paths(&Once);
+        paths.sort();
+        assert_eq!(paths.len(), 10);
+        assert_eq!(paths[0], ["start", "A", "b", "A", "c", "A", "end"]);
+        assert_eq!(caves.paths(&OneTwice).len(), 36);
+
+        // at most once is the first policy, at most twice allows more
+        assert_eq!(caves.count(&AtMost(1)), 10);
+        assert!(caves.count(&AtMost(2)) > 36);
+        assert_eq!(
+            caves.paths(&AtMost(2)).len() as u64,
+            caves.count(&AtMost(2))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Caves::parse("start-A\nA-B\nB-end"),
+            Err(caves::ParseError::LinkedBigCaves(..))
+        ));
+        assert!(matches!(
+            Caves::parse("start-a"),
+            Err(caves::ParseError::MissingCave("end"))
+        ));
+        assert!(matches!(
+            Caves::parse("start-a\nend"),
+            Err(caves::ParseError::InvalidLine(_))
+        ));
     }
 }
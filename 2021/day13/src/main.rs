@@ -1,125 +1,77 @@
-use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
+mod origami;
 
-type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
-
-#[derive(Debug, Copy, Clone)]
-enum Fold {
-    X(usize),
-    Y(usize),
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct Dot {
-    x: usize,
-    y: usize,
-}
-
-impl Hash for Dot {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.x.hash(state);
-        self.y.hash(state);
-    }
-}
-
-fn do_fold(dots: &mut [Dot], fold: Fold) {
-    for dot in dots {
-        match fold {
-            Fold::X(x) => dot.x = if dot.x < x { dot.x } else { x + x - dot.x },
-            Fold::Y(y) => dot.y = if dot.y < y { dot.y } else { y + y - dot.y },
-        }
-    }
-}
-
-fn parse_input() -> Result<(Vec<Dot>, Vec<Fold>)> {
-    let (dots, folds) = include_str!("../input.txt")
-        .split_once("\n\n")
-        .ok_or("missing instructions")?;
+use origami::Paper;
 
-    let dots = dots
-        .lines()
-        .filter_map(|l| {
-            l.split_once(',').map(|(x, y)| {
-                Ok(Dot {
-                    x: x.parse()?,
-                    y: y.parse()?,
-                })
-            })
-        })
-        .collect::<Result<_>>()?;
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-    let folds = folds
-        .lines()
-        .filter_map(|l| {
-            l.split_once('=').map(|(xy, pos)| match xy.chars().last() {
-                Some(c) => {
-                    if c == 'x' {
-                        Ok(Fold::X(pos.parse()?))
-                    } else if c == 'y' {
-                        Ok(Fold::Y(pos.parse()?))
-                    } else {
-                        Err("missing fold along instruction".into())
-                    }
-                }
-                _ => Err("missing instruction".into()),
-            })
-        })
-        .collect::<Result<_>>()?;
+fn part1(input: &str) -> Result<usize> {
+    let (paper, folds) = origami::parse(input)?;
+    let fold = *folds.first().ok_or("missing first fold instruction")?;
 
-    Ok((dots, folds))
+    Ok(paper.fold(fold)?.dots().len())
 }
 
-fn part1() -> Result<usize> {
-    let (mut dots, folds) = parse_input()?;
-
-    do_fold(
-        &mut dots,
-        folds
-            .first()
-            .ok_or("missing first fold instruction")?
-            .clone(),
-    );
+fn part2(input: &str) -> Result<Paper> {
+    let (paper, folds) = origami::parse(input)?;
+    let papers = paper
+        .fold_all(&folds)
+        .map_err(|(i, e)| format!("fold {}: {e}", i + 1))?;
 
-    Ok(dots.into_iter().collect::<HashSet<_>>().len())
+    Ok(papers.into_iter().last().unwrap_or(paper))
 }
 
-fn part2() -> Result<usize> {
-    let (mut dots, folds) = parse_input()?;
-
-    for fold in folds {
-        do_fold(&mut dots, fold);
-    }
-
-    let (width, height) = dots.iter().fold((0, 0), |(width, height), dot| {
-        (dot.x.max(width), dot.y.max(height))
-    });
-    let mut code = vec![vec!['.'; width + 1]; height + 1];
-    for dot in dots {
-        code[dot.y][dot.x] = '#';
-    }
-    for y in 0..=height {
-        for x in 0..=width {
-            print!("{}", code[y][x]);
-        }
-        println!("");
-    }
-
-    Ok(0)
+fn main() -> Result<()> {
+    println!("part1={}", part1(include_str!("../input.txt"))?);
+    print!("part2=\n{}", part2(include_str!("../input.txt"))?);
+    Ok(())
 }
 
-fn main() {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    const TEST_INPUT: &str = "6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1().unwrap());
+        assert_eq!(part1(TEST_INPUT).unwrap(), 17);
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2().unwrap());
+        assert_eq!(
+            part2(TEST_INPUT).unwrap().to_string(),
+            "#####\n#...#\n#...#\n#...#\n#####\n.....\n.....\n"
+        );
+    }
+
+    #[test]
+    fn test_intermediate() {
+        let (paper, folds) = origami::parse(TEST_INPUT).unwrap();
+        let papers = paper.fold_all(&folds).unwrap();
+        assert_eq!(papers.len(), 2);
+        assert_eq!((papers[0].width, papers[0].height), (11, 7));
+        assert_eq!(papers[0].dots().len(), 17);
+        assert_eq!(papers[1].dots().len(), 16);
+        assert_eq!(papers[0].to_string().lines().next(), Some("#.##..#..#."));
     }
 }
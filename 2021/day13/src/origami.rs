@@ -0,0 +1,265 @@
+use std::{collections::BTreeSet, fmt};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fold {
+    X(usize),
+    Y(usize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dot {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidDot(String),
+    InvalidFold(String),
+    MissingFolds,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidDot(line) => write!(f, "invalid dot {line:?}"),
+            ParseError::InvalidFold(line) => write!(f, "invalid fold {line:?}"),
+            ParseError::MissingFolds => write!(f, "missing fold instructions"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FoldError {
+    /// The line is past the edge of the paper
+    OutsidePaper {
+        fold: Fold,
+        size: usize,
+    },
+    DotOnLine {
+        fold: Fold,
+        dot: Dot,
+    },
+}
+
+impl fmt::Display for FoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoldError::OutsidePaper { fold, size } => {
+                write!(f, "{fold:?} is outside of the paper of size {size}")
+            }
+            FoldError::DotOnLine { fold, dot } => {
+                write!(f, "dot {},{} is on the line of {fold:?}", dot.x, dot.y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FoldError {}
+
+/// A sheet of transparent paper, as big as its dots need unless it was folded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paper {
+    dots: BTreeSet<Dot>,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Position of `pos` after folding a side of length `size` along `line`.
+/// When the folded part is longer than the kept one it goes past the edge,
+/// which then becomes position 0.
+fn fold_position(pos: usize, line: usize, size: usize) -> usize {
+    let folded = size - 1 - line;
+    let new_size = line.max(folded);
+    if pos < line {
+        pos + new_size - line
+    } else {
+        new_size - (pos - line)
+    }
+}
+
+impl Paper {
+    pub fn new(dots: impl IntoIterator<Item = Dot>) -> Self {
+        let dots = dots.into_iter().collect::<BTreeSet<_>>();
+        let width = dots.iter().map(|d| d.x + 1).max().unwrap_or(0);
+        let height = dots.iter().map(|d| d.y + 1).max().unwrap_or(0);
+
+        Paper {
+            dots,
+            width,
+            height,
+        }
+    }
+
+    pub fn dots(&self) -> &BTreeSet<Dot> {
+        &self.dots
+    }
+
+    /// The paper folded up or left along the line. Folds off the midline are
+    /// fine, dots on the line aren't.
+    pub fn fold(&self, fold: Fold) -> Result<Paper, FoldError> {
+        let (line, size) = match fold {
+            Fold::X(x) => (x, self.width),
+            Fold::Y(y) => (y, self.height),
+        };
+        if line >= size {
+            return Err(FoldError::OutsidePaper { fold, size });
+        }
+        let on_line = |dot: &&Dot| match fold {
+            Fold::X(x) => dot.x == x,
+            Fold::Y(y) => dot.y == y,
+        };
+        if let Some(&dot) = self.dots.iter().find(on_line) {
+            return Err(FoldError::DotOnLine { fold, dot });
+        }
+
+        let new_size = line.max(size - 1 - line);
+        let dots = self
+            .dots
+            .iter()
+            .map(|&Dot { x, y }| match fold {
+                Fold::X(_) => Dot {
+                    x: fold_position(x, line, size),
+                    y,
+                },
+                Fold::Y(_) => Dot {
+                    x,
+                    y: fold_position(y, line, size),
+                },
+            })
+            .collect();
+
+        Ok(match fold {
+            Fold::X(_) => Paper {
+                dots,
+                width: new_size,
+                height: self.height,
+            },
+            Fold::Y(_) => Paper {
+                dots,
+                width: self.width,
+                height: new_size,
+            },
+        })
+    }
+
+    /// The paper after each of `folds`, with the index of the fold that
+    /// failed if any.
+    pub fn fold_all(&self, folds: &[Fold]) -> Result<Vec<Paper>, (usize, FoldError)> {
+        let mut papers: Vec<Paper> = Vec::with_capacity(folds.len());
+        for (i, &fold) in folds.iter().enumerate() {
+            let paper = papers
+                .last()
+                .unwrap_or(self)
+                .fold(fold)
+                .map_err(|e| (i, e))?;
+            papers.push(paper);
+        }
+
+        Ok(papers)
+    }
+}
+
+impl fmt::Display for Paper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = if self.dots.contains(&Dot { x, y }) {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The dots, a blank line and `fold along x=5` instructions.
+pub fn parse(input: &str) -> Result<(Paper, Vec<Fold>), ParseError> {
+    let (dots, folds) = input.split_once("\n\n").ok_or(ParseError::MissingFolds)?;
+
+    let dots = dots
+        .lines()
+        .map(|l| {
+            l.split_once(',')
+                .and_then(|(x, y)| {
+                    Some(Dot {
+                        x: x.parse().ok()?,
+                        y: y.parse().ok()?,
+                    })
+                })
+                .ok_or_else(|| ParseError::InvalidDot(l.to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let folds = folds
+        .lines()
+        .map(|l| {
+            let fold = l
+                .strip_prefix("fold along ")
+                .and_then(|l| l.split_once('='))
+                .and_then(|(axis, pos)| match (axis, pos.parse().ok()?) {
+                    ("x", pos) => Some(Fold::X(pos)),
+                    ("y", pos) => Some(Fold::Y(pos)),
+                    _ => None,
+                });
+            fold.ok_or_else(|| ParseError::InvalidFold(l.to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((Paper::new(dots), folds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_off_midline() {
+        let paper = Paper::new([Dot { x: 0, y: 0 }, Dot { x: 4, y: 1 }, Dot { x: 2, y: 1 }]);
+        assert_eq!(paper.width, 5);
+
+        // the right part is longer and goes past the left edge
+        let folded = paper.fold(Fold::X(1)).unwrap();
+        assert_eq!(folded.width, 3);
+        assert_eq!(folded.to_string(), "..#\n#.#\n");
+
+        assert_eq!(
+            paper.fold(Fold::X(2)),
+            Err(FoldError::DotOnLine {
+                fold: Fold::X(2),
+                dot: Dot { x: 2, y: 1 }
+            })
+        );
+        assert_eq!(
+            paper.fold(Fold::Y(2)),
+            Err(FoldError::OutsidePaper {
+                fold: Fold::Y(2),
+                size: 2
+            })
+        );
+        assert!(matches!(
+            paper.fold_all(&[Fold::X(3), Fold::Y(5)]),
+            Err((1, FoldError::OutsidePaper { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("1,2\n"), Err(ParseError::MissingFolds));
+        assert_eq!(
+            parse("1,2\n\nfold along z=3"),
+            Err(ParseError::InvalidFold("fold along z=3".to_owned()))
+        );
+        assert_eq!(
+            parse("1;2\n\nfold along x=3"),
+            Err(ParseError::InvalidDot("1;2".to_owned()))
+        );
+    }
+}
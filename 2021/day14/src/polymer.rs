@@ -0,0 +1,330 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU64,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Number of each element in the polymer, absent elements aren't listed.
+pub type Histogram = BTreeMap<char, u64>;
+
+type Matrix = Vec<Vec<u128>>;
+
+/// Pair insertion over any alphabet. Only the pairs which can appear starting
+/// from the template are tracked.
+#[derive(Debug)]
+pub struct Polymer {
+    alphabet: Vec<char>,
+    // reachable pairs of element indexes and their index in the counters
+    pairs: Vec<(usize, usize)>,
+    // for each pair, the pairs it turns into at the next step
+    produces: Vec<Vec<usize>>,
+    template_pairs: Vec<u64>,
+    // the last element never changes, it isn't the left side of any pair
+    last: usize,
+}
+
+impl Polymer {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (template, rules) = input.split_once("\n\n").ok_or("missing rules")?;
+        let template = template.trim().chars().collect::<Vec<_>>();
+        if template.is_empty() {
+            return Err("empty template".into());
+        }
+
+        let mut alphabet = Vec::new();
+        let mut index = HashMap::new();
+        let mut idx = |c: char| {
+            *index.entry(c).or_insert_with(|| {
+                alphabet.push(c);
+                alphabet.len() - 1
+            })
+        };
+
+        let template = template.into_iter().map(&mut idx).collect::<Vec<_>>();
+        let mut insertions = HashMap::new();
+        for line in rules.lines().filter(|l| !l.trim().is_empty()) {
+            let (pair, element) = line
+                .split_once(" -> ")
+                .ok_or_else(|| format!("invalid rule {line:?}"))?;
+            let (pair, element) = (
+                pair.chars().collect::<Vec<_>>(),
+                element.chars().collect::<Vec<_>>(),
+            );
+            match (&pair[..], &element[..]) {
+                (&[left, right], &[element]) => {
+                    insertions.insert((idx(left), idx(right)), idx(element));
+                }
+                _ => return Err(format!("invalid rule {line:?}").into()),
+            }
+        }
+
+        // explore the pairs reachable from the template
+        let mut ids: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut pairs = Vec::new();
+        let mut todo = template
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .collect::<Vec<_>>();
+        while let Some(pair) = todo.pop() {
+            if ids.contains_key(&pair) {
+                continue;
+            }
+            ids.insert(pair, pairs.len());
+            pairs.push(pair);
+            if let Some(&element) = insertions.get(&pair) {
+                todo.push((pair.0, element));
+                todo.push((element, pair.1));
+            }
+        }
+
+        let produces = pairs
+            .iter()
+            .map(|&(left, right)| match insertions.get(&(left, right)) {
+                Some(&element) => vec![ids[&(left, element)], ids[&(element, right)]],
+                None => vec![ids[&(left, right)]],
+            })
+            .collect();
+        let mut template_pairs = vec![0; pairs.len()];
+        for w in template.windows(2) {
+            template_pairs[ids[&(w[0], w[1])]] += 1;
+        }
+
+        Ok(Polymer {
+            last: *template.last().unwrap(),
+            alphabet,
+            pairs,
+            produces,
+            template_pairs,
+        })
+    }
+
+    fn histogram_of(
+        &self,
+        counts: impl Iterator<Item = u128>,
+        modulus: Option<u128>,
+    ) -> Option<Histogram> {
+        let mut elements = vec![0u128; self.alphabet.len()];
+        elements[self.last] = 1;
+        for (&(left, _), count) in self.pairs.iter().zip(counts) {
+            elements[left] += count;
+            if let Some(m) = modulus {
+                elements[left] %= m;
+            }
+        }
+
+        self.alphabet
+            .iter()
+            .zip(elements)
+            .filter(|&(_, count)| count != 0)
+            .map(|(&c, count)| Some((c, u64::try_from(count).ok()?)))
+            .collect()
+    }
+
+    /// Element histograms after each step, starting with the template. Stops
+    /// when counts no longer fit in a u64.
+    pub fn steps(&self) -> impl Iterator<Item = Histogram> + '_ {
+        let mut counts = Some(
+            self.template_pairs
+                .iter()
+                .map(|&c| c as u128)
+                .collect::<Vec<_>>(),
+        );
+
+        std::iter::from_fn(move || {
+            let current = counts.take()?;
+            let histogram = self.histogram_of(current.iter().copied(), None)?;
+
+            let mut next = vec![0u128; current.len()];
+            for (pair, &count) in current.iter().enumerate() {
+                for &produced in &self.produces[pair] {
+                    next[produced] += count;
+                }
+            }
+            counts = Some(next);
+
+            Some(histogram)
+        })
+    }
+
+    fn transition(&self) -> Matrix {
+        let n = self.pairs.len();
+        let mut matrix = vec![vec![0; n]; n];
+        for (pair, produced) in self.produces.iter().enumerate() {
+            for &p in produced {
+                matrix[pair][p] += 1;
+            }
+        }
+
+        matrix
+    }
+
+    fn run(&self, steps: u64, modulus: Option<u128>) -> Option<Histogram> {
+        let power = matrix_pow(self.transition(), steps, modulus)?;
+        let counts = (0..self.pairs.len())
+            .map(|col| {
+                self.template_pairs
+                    .iter()
+                    .zip(&power)
+                    .try_fold(0u128, |acc, (&count, row)| {
+                        add(acc, mul(count as u128, row[col], modulus)?, modulus)
+                    })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        self.histogram_of(counts.into_iter(), modulus)
+    }
+
+    /// Element histogram after `steps` steps, by exponentiating the transition
+    /// matrix between pairs. `None` if the counts overflow.
+    pub fn histogram(&self, steps: u64) -> Option<Histogram> {
+        self.run(steps, None)
+    }
+
+    /// Element histogram after `steps` steps, counts modulo `modulus`.
+    pub fn histogram_mod(&self, steps: u64, modulus: NonZeroU64) -> Histogram {
+        self.run(steps, Some(modulus.get() as u128)).unwrap()
+    }
+}
+
+fn add(a: u128, b: u128, modulus: Option<u128>) -> Option<u128> {
+    match modulus {
+        Some(m) => Some((a + b) % m),
+        None => a.checked_add(b),
+    }
+}
+
+fn mul(a: u128, b: u128, modulus: Option<u128>) -> Option<u128> {
+    match modulus {
+        Some(m) => Some(a % m * (b % m) % m),
+        None => a.checked_mul(b),
+    }
+}
+
+fn matrix_mul(a: &Matrix, b: &Matrix, modulus: Option<u128>) -> Option<Matrix> {
+    let n = a.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    (0..n).try_fold(0, |acc, k| {
+                        add(acc, mul(a[i][k], b[k][j], modulus)?, modulus)
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn matrix_pow(mut base: Matrix, mut exp: u64, modulus: Option<u128>) -> Option<Matrix> {
+    let n = base.len();
+    let mut result = (0..n)
+        .map(|i| (0..n).map(|j| (i == j) as u128).collect())
+        .collect::<Matrix>();
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = matrix_mul(&result, &base, modulus)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = matrix_mul(&base, &base, modulus)?;
+        }
+    }
+
+    Some(result)
+}
+
+/// Difference between the most and least common elements.
+pub fn spread(histogram: &Histogram) -> u64 {
+    let (min, max) = histogram
+        .values()
+        .fold((u64::MAX, 0), |(min, max), &c| (min.min(c), max.max(c)));
+
+    max.saturating_sub(min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_INPUT: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
+    #[test]
+    fn test_steps() {
+        let polymer = Polymer::parse(TEST_INPUT).unwrap();
+        let steps = polymer.steps().take(11).collect::<Vec<_>>();
+
+        // NCNBCHB
+        assert_eq!(
+            steps[1],
+            Histogram::from([('N', 2), ('C', 2), ('B', 2), ('H', 1)])
+        );
+        assert_eq!(
+            steps[10],
+            Histogram::from([('N', 865), ('C', 298), ('B', 1749), ('H', 161)])
+        );
+        for (i, histogram) in steps.iter().enumerate() {
+            assert_eq!(polymer.histogram(i as u64).as_ref(), Some(histogram));
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_alphabet() {
+        let polymer = Polymer::parse("a-1\n\na- -> 1\n-1 -> a\n1a -> -\naa -> a").unwrap();
+        let steps = polymer.steps().take(8).collect::<Vec<_>>();
+        assert_eq!(steps[0], Histogram::from([('a', 1), ('-', 1), ('1', 1)]));
+        // a1-a1
+        assert_eq!(steps[1], Histogram::from([('a', 2), ('-', 1), ('1', 2)]));
+        assert_eq!(polymer.histogram(7).as_ref(), Some(&steps[7]));
+    }
+
+    #[test]
+    fn test_large_steps() {
+        // aa -> aaa -> aaaaa: 2^n + 1 elements after n steps
+        let polymer = Polymer::parse("aa\n\naa -> a").unwrap();
+        assert_eq!(
+            polymer.histogram(63),
+            Some(Histogram::from([('a', (1 << 63) + 1)]))
+        );
+        assert_eq!(polymer.histogram(64), None);
+
+        const P: u64 = 1_000_000_007;
+        let modulus = NonZeroU64::new(P).unwrap();
+        let pow2 = (0..40).fold((1u64, 2u64), |(acc, base), bit| {
+            let acc = if (1_000_000_000_000u64 >> bit) & 1 == 1 {
+                acc * base % P
+            } else {
+                acc
+            };
+            (acc, base * base % P)
+        });
+        assert_eq!(
+            polymer.histogram_mod(1_000_000_000_000, modulus),
+            Histogram::from([('a', (pow2.0 + 1) % P)])
+        );
+
+        let polymer = Polymer::parse(TEST_INPUT).unwrap();
+        let exact = polymer.histogram(40).unwrap();
+        let modular = polymer.histogram_mod(40, modulus);
+        for (c, count) in exact {
+            assert_eq!(modular.get(&c).copied().unwrap_or(0), count % P);
+        }
+    }
+}
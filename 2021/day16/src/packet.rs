@@ -0,0 +1,616 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Op {
+    fn from_typeid(typeid: u8) -> Option<Op> {
+        match typeid {
+            0 => Some(Op::Sum),
+            1 => Some(Op::Product),
+            2 => Some(Op::Minimum),
+            3 => Some(Op::Maximum),
+            5 => Some(Op::GreaterThan),
+            6 => Some(Op::LessThan),
+            7 => Some(Op::EqualTo),
+            _ => None,
+        }
+    }
+
+    fn typeid(self) -> u8 {
+        match self {
+            Op::Sum => 0,
+            Op::Product => 1,
+            Op::Minimum => 2,
+            Op::Maximum => 3,
+            Op::GreaterThan => 5,
+            Op::LessThan => 6,
+            Op::EqualTo => 7,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Op::Sum => "sum",
+            Op::Product => "product",
+            Op::Minimum => "min",
+            Op::Maximum => "max",
+            Op::GreaterThan => ">",
+            Op::LessThan => "<",
+            Op::EqualTo => "=",
+        }
+    }
+}
+
+/// How an operator packet encodes the size of its subpackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// 15 bits: total length in bits of the subpackets
+    Bits,
+    /// 11 bits: number of subpackets
+    Packets,
+}
+
+const LITERAL_TYPEID: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Literal {
+        version: u8,
+        literal: u64,
+    },
+    Operator {
+        version: u8,
+        op: Op,
+        length_type: LengthType,
+        subpackets: Vec<Packet>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidHex(char),
+    /// The stream ended while `needed` more bits were expected at `offset`.
+    Truncated {
+        offset: usize,
+        needed: usize,
+    },
+    /// Non-zero bits after the outermost packet.
+    TrailingGarbage {
+        offset: usize,
+    },
+    /// Subpackets overran the length given by their operator.
+    LengthMismatch {
+        offset: usize,
+    },
+    LiteralOverflow {
+        offset: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    VersionOverflow(u8),
+    TooManySubpackets(usize),
+    SubpacketsTooLong(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    NoOperand(Op),
+    Arity { op: Op, found: usize },
+    Overflow(Op),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex(c) => write!(f, "invalid hex digit {c:?}"),
+            DecodeError::Truncated { offset, needed } => {
+                write!(
+                    f,
+                    "truncated stream: {needed} more bits needed at bit {offset}"
+                )
+            }
+            DecodeError::TrailingGarbage { offset } => {
+                write!(f, "trailing garbage after the packet at bit {offset}")
+            }
+            DecodeError::LengthMismatch { offset } => {
+                write!(f, "subpackets overrun their length at bit {offset}")
+            }
+            DecodeError::LiteralOverflow { offset } => {
+                write!(f, "literal doesn't fit in 64 bits at bit {offset}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::VersionOverflow(v) => write!(f, "version {v} doesn't fit in 3 bits"),
+            EncodeError::TooManySubpackets(n) => {
+                write!(f, "{n} subpackets don't fit in the 11 bits count")
+            }
+            EncodeError::SubpacketsTooLong(n) => {
+                write!(f, "{n} bits of subpackets don't fit in the 15 bits length")
+            }
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::NoOperand(op) => write!(f, "{} without operand", op.name()),
+            EvalError::Arity { op, found } => {
+                write!(f, "{} expects 2 operands, found {found}", op.name())
+            }
+            EvalError::Overflow(op) => write!(f, "{} overflows 64 bits", op.name()),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+impl std::error::Error for EncodeError {}
+impl std::error::Error for EvalError {}
+
+struct BitReader {
+    bits: Vec<u8>,
+    pos: usize,
+}
+
+impl BitReader {
+    fn from_hex(hex: &str) -> Result<Self, DecodeError> {
+        let bits = hex
+            .trim()
+            .chars()
+            .map(|c| c.to_digit(16).ok_or(DecodeError::InvalidHex(c)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flat_map(|d| (0..4).rev().map(move |i| (d as u8 >> i) & 1))
+            .collect();
+
+        Ok(BitReader { bits, pos: 0 })
+    }
+
+    fn read(&mut self, nr_bits: usize) -> Result<u64, DecodeError> {
+        let end = self.pos + nr_bits;
+        if end > self.bits.len() {
+            return Err(DecodeError::Truncated {
+                offset: self.pos,
+                needed: end - self.bits.len(),
+            });
+        }
+
+        let ret = self.bits[self.pos..end]
+            .iter()
+            .fold(0, |acc, &bit| acc << 1 | bit as u64);
+        self.pos = end;
+
+        Ok(ret)
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bits: Vec<u8>,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, nr_bits: usize) {
+        self.bits
+            .extend((0..nr_bits).rev().map(|i| ((value >> i) & 1) as u8));
+    }
+
+    fn to_hex(&self) -> String {
+        // pad to whole bytes, like the puzzle transmissions
+        let padding = (8 - self.bits.len() % 8) % 8;
+        self.bits
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(0, padding))
+            .collect::<Vec<_>>()
+            .chunks(4)
+            .map(|nibble| {
+                let d = nibble.iter().fold(0, |acc, &bit| acc << 1 | bit as u32);
+                char::from_digit(d, 16).unwrap().to_ascii_uppercase()
+            })
+            .collect()
+    }
+}
+
+impl Packet {
+    /// Decode a transmission made of a single packet, padded with zeros.
+    pub fn from_hex(hex: &str) -> Result<Packet, DecodeError> {
+        let mut reader = BitReader::from_hex(hex)?;
+        let packet = Packet::decode(&mut reader)?;
+
+        match reader.bits[reader.pos..].iter().position(|&bit| bit != 0) {
+            Some(i) => Err(DecodeError::TrailingGarbage {
+                offset: reader.pos + i,
+            }),
+            None => Ok(packet),
+        }
+    }
+
+    fn decode(reader: &mut BitReader) -> Result<Packet, DecodeError> {
+        let version = reader.read(3)? as u8;
+        let typeid = reader.read(3)? as u8;
+
+        let Some(op) = Op::from_typeid(typeid) else {
+            let offset = reader.pos;
+            let mut literal: u64 = 0;
+            loop {
+                let more = reader.read(1)?;
+                let group = reader.read(4)?;
+                if literal.leading_zeros() < 4 {
+                    return Err(DecodeError::LiteralOverflow { offset });
+                }
+                literal = literal << 4 | group;
+                if more == 0 {
+                    break;
+                }
+            }
+            return Ok(Packet::Literal { version, literal });
+        };
+
+        let (length_type, subpackets) = if reader.read(1)? == 0 {
+            let len = reader.read(15)? as usize;
+            let end = reader.pos + len;
+            let mut pkts = Vec::new();
+            while reader.pos < end {
+                pkts.push(Packet::decode(reader)?);
+            }
+            if reader.pos > end {
+                return Err(DecodeError::LengthMismatch { offset: end });
+            }
+            (LengthType::Bits, pkts)
+        } else {
+            let nr_packets = reader.read(11)?;
+            let pkts = (0..nr_packets)
+                .map(|_| Packet::decode(reader))
+                .collect::<Result<_, _>>()?;
+            (LengthType::Packets, pkts)
+        };
+
+        Ok(Packet::Operator {
+            version,
+            op,
+            length_type,
+            subpackets,
+        })
+    }
+
+    pub fn to_hex(&self) -> Result<String, EncodeError> {
+        let mut writer = BitWriter::default();
+        self.encode(&mut writer)?;
+        Ok(writer.to_hex())
+    }
+
+    fn encode(&self, writer: &mut BitWriter) -> Result<(), EncodeError> {
+        let version = match self {
+            Packet::Literal { version, .. } | Packet::Operator { version, .. } => *version,
+        };
+        if version >= 8 {
+            return Err(EncodeError::VersionOverflow(version));
+        }
+        writer.write(version as u64, 3);
+
+        match self {
+            Packet::Literal { literal, .. } => {
+                writer.write(LITERAL_TYPEID as u64, 3);
+                let nr_groups = (64 - literal.leading_zeros() as usize).div_ceil(4).max(1);
+                for i in (0..nr_groups).rev() {
+                    writer.write((i > 0) as u64, 1);
+                    writer.write(literal >> (4 * i) & 0xf, 4);
+                }
+            }
+            Packet::Operator {
+                op,
+                length_type,
+                subpackets,
+                ..
+            } => {
+                writer.write(op.typeid() as u64, 3);
+                match length_type {
+                    LengthType::Bits => {
+                        let mut sub = BitWriter::default();
+                        for packet in subpackets {
+                            packet.encode(&mut sub)?;
+                        }
+                        if sub.bits.len() >= 1 << 15 {
+                            return Err(EncodeError::SubpacketsTooLong(sub.bits.len()));
+                        }
+                        writer.write(0, 1);
+                        writer.write(sub.bits.len() as u64, 15);
+                        writer.bits.extend(sub.bits);
+                    }
+                    LengthType::Packets => {
+                        if subpackets.len() >= 1 << 11 {
+                            return Err(EncodeError::TooManySubpackets(subpackets.len()));
+                        }
+                        writer.write(1, 1);
+                        writer.write(subpackets.len() as u64, 11);
+                        for packet in subpackets {
+                            packet.encode(writer)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn sum_versions(&self) -> u32 {
+        match self {
+            Packet::Literal { version, .. } => *version as u32,
+            Packet::Operator {
+                version,
+                subpackets,
+                ..
+            } => *version as u32 + subpackets.iter().map(Packet::sum_versions).sum::<u32>(),
+        }
+    }
+
+    pub fn eval(&self) -> Result<u64, EvalError> {
+        let (op, subpackets) = match self {
+            Packet::Literal { literal, .. } => return Ok(*literal),
+            Packet::Operator { op, subpackets, .. } => (*op, subpackets),
+        };
+        let values = subpackets
+            .iter()
+            .map(Packet::eval)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match op {
+            Op::Sum => values
+                .into_iter()
+                .try_fold(0u64, |acc, v| acc.checked_add(v))
+                .ok_or(EvalError::Overflow(op)),
+            Op::Product => values
+                .into_iter()
+                .try_fold(1u64, |acc, v| acc.checked_mul(v))
+                .ok_or(EvalError::Overflow(op)),
+            Op::Minimum => values.into_iter().min().ok_or(EvalError::NoOperand(op)),
+            Op::Maximum => values.into_iter().max().ok_or(EvalError::NoOperand(op)),
+            Op::GreaterThan | Op::LessThan | Op::EqualTo => match values[..] {
+                [first, second] => Ok(match op {
+                    Op::GreaterThan => first > second,
+                    Op::LessThan => first < second,
+                    _ => first == second,
+                } as u64),
+                _ => Err(EvalError::Arity {
+                    op,
+                    found: values.len(),
+                }),
+            },
+        }
+    }
+}
+
+/// S-expression, e.g. `(sum 1 (max 2 3))`. The alternate flag `{:#}` puts
+/// each operand on its own indented line.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write(packet: &Packet, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+            match packet {
+                Packet::Literal { literal, .. } => write!(f, "{literal}"),
+                Packet::Operator { op, subpackets, .. } => {
+                    write!(f, "({}", op.name())?;
+                    for packet in subpackets {
+                        if f.alternate() {
+                            write!(f, "\n{:width$}", "", width = 2 * (depth + 1))?;
+                        } else {
+                            write!(f, " ")?;
+                        }
+                        write(packet, f, depth + 1)?;
+                    }
+                    write!(f, ")")
+                }
+            }
+        }
+
+        write(self, f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, enough to generate packet trees without pulling a crate
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    fn random_packet(rng: &mut Rng, depth: usize) -> Packet {
+        let version = rng.below(8) as u8;
+        if depth == 0 || rng.below(3) == 0 {
+            let literal = rng.next() >> rng.below(64);
+            return Packet::Literal { version, literal };
+        }
+
+        let ops = [
+            Op::Sum,
+            Op::Product,
+            Op::Minimum,
+            Op::Maximum,
+            Op::GreaterThan,
+            Op::LessThan,
+            Op::EqualTo,
+        ];
+        let length_type = if rng.below(2) == 0 {
+            LengthType::Bits
+        } else {
+            LengthType::Packets
+        };
+        let nr_subpackets = rng.below(5);
+
+        Packet::Operator {
+            version,
+            op: ops[rng.below(ops.len() as u64) as usize],
+            length_type,
+            subpackets: (0..nr_subpackets)
+                .map(|_| random_packet(rng, depth - 1))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_samples_round_trip() {
+        for sample in [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+            "9C0141080250320F1802104A08",
+        ] {
+            let packet = Packet::from_hex(sample).unwrap();
+            assert_eq!(packet.to_hex().unwrap(), sample);
+        }
+    }
+
+    #[test]
+    fn test_random_round_trip() {
+        let mut rng = Rng(0x2021_0016);
+
+        for _ in 0..500 {
+            let packet = random_packet(&mut rng, 4);
+            let hex = packet.to_hex().unwrap();
+            assert_eq!(Packet::from_hex(&hex), Ok(packet.clone()));
+            assert_eq!(Packet::from_hex(&format!("{hex}00")), Ok(packet.clone()));
+
+            assert!(matches!(
+                Packet::from_hex(&hex[..hex.len() - 2]),
+                Err(DecodeError::Truncated { .. })
+            ));
+            assert!(matches!(
+                Packet::from_hex(&format!("{hex}01")),
+                Err(DecodeError::TrailingGarbage { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Packet::from_hex("D2FG28"),
+            Err(DecodeError::InvalidHex('G'))
+        );
+        assert_eq!(
+            Packet::from_hex("D2FE"),
+            Err(DecodeError::Truncated {
+                offset: 16,
+                needed: 1
+            })
+        );
+        assert_eq!(
+            Packet::from_hex("D2FE28F0"),
+            Err(DecodeError::TrailingGarbage { offset: 24 })
+        );
+        // literal with 17 groups of 4 bits
+        let mut writer = BitWriter::default();
+        writer.write(4, 6);
+        for _ in 0..16 {
+            writer.write(0x1f, 5);
+        }
+        writer.write(0x0f, 5);
+        assert!(matches!(
+            Packet::from_hex(&writer.to_hex()),
+            Err(DecodeError::LiteralOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let packet = Packet::Literal {
+            version: 8,
+            literal: 0,
+        };
+        assert_eq!(packet.to_hex(), Err(EncodeError::VersionOverflow(8)));
+
+        let packet = Packet::Operator {
+            version: 0,
+            op: Op::Sum,
+            length_type: LengthType::Packets,
+            subpackets: vec![
+                Packet::Literal {
+                    version: 0,
+                    literal: 1
+                };
+                2048
+            ],
+        };
+        assert_eq!(packet.to_hex(), Err(EncodeError::TooManySubpackets(2048)));
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let packet = Packet::from_hex("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(packet.to_string(), "(= (sum 1 3) (product 2 2))");
+        assert_eq!(
+            format!("{packet:#}"),
+            "(=
+  (sum
+    1
+    3)
+  (product
+    2
+    2))"
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let lit = |literal| Packet::Literal {
+            version: 0,
+            literal,
+        };
+        let op = |op, subpackets| Packet::Operator {
+            version: 0,
+            op,
+            length_type: LengthType::Packets,
+            subpackets,
+        };
+
+        assert_eq!(
+            op(Op::LessThan, vec![lit(1)]).eval(),
+            Err(EvalError::Arity {
+                op: Op::LessThan,
+                found: 1
+            })
+        );
+        assert_eq!(
+            op(Op::Minimum, vec![]).eval(),
+            Err(EvalError::NoOperand(Op::Minimum))
+        );
+        assert_eq!(
+            op(Op::Product, vec![lit(u64::MAX), lit(2)]).eval(),
+            Err(EvalError::Overflow(Op::Product))
+        );
+    }
+}
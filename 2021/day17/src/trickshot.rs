@@ -0,0 +1,300 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetError {
+    Format(String),
+    Empty,
+    /// The probe would be in the target each time it falls back to the
+    /// launch height, for infinitely many velocities
+    AtLaunchHeight,
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetError::Format(s) => write!(f, "invalid target area {s:?}"),
+            TargetError::Empty => write!(f, "empty target area"),
+            TargetError::AtLaunchHeight => write!(f, "target area at the launch height"),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+/// The target area, bounds included. It can be on any side of the launch
+/// position, but not at its height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub x_min: i32,
+    pub x_max: i32,
+    pub y_min: i32,
+    pub y_max: i32,
+}
+
+/// Steps `first..=last` during which the probe is within some bounds, `last`
+/// being `None` if it stays there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub first: i64,
+    pub last: Option<i64>,
+}
+
+impl Window {
+    fn new(first: i64, last: Option<i64>) -> Option<Self> {
+        last.is_none_or(|last| first <= last)
+            .then_some(Window { first, last })
+    }
+
+    pub fn intersect(self, other: Window) -> Option<Window> {
+        let last = match (self.last, other.last) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Window::new(self.first.max(other.first), last)
+    }
+}
+
+/// First step of `from..=to` where `pred` holds, `pred` being false then true.
+fn first_step(from: i64, to: i64, pred: impl Fn(i64) -> bool) -> Option<i64> {
+    let (mut lo, mut hi) = (from, to + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+
+    (lo <= to).then_some(lo)
+}
+
+/// Distance covered after `t` steps at speed `v`, slowing down by one each
+/// step until it stops.
+fn distance(v: i64, t: i64) -> i64 {
+    let t = t.min(v);
+    t * v - t * (t - 1) / 2
+}
+
+/// Height after `t` steps at vertical speed `v`.
+fn height(v: i64, t: i64) -> i64 {
+    t * v - t * (t - 1) / 2
+}
+
+impl Target {
+    pub fn new(x: (i32, i32), y: (i32, i32)) -> Result<Self, TargetError> {
+        if x.0 > x.1 || y.0 > y.1 {
+            return Err(TargetError::Empty);
+        }
+        if y.0 <= 0 && y.1 >= 0 {
+            return Err(TargetError::AtLaunchHeight);
+        }
+
+        Ok(Target {
+            x_min: x.0,
+            x_max: x.1,
+            y_min: y.0,
+            y_max: y.1,
+        })
+    }
+
+    /// Steps during which the probe launched at horizontal speed `vx` is
+    /// above or below the target. The distance only grows, so there is at
+    /// most one window.
+    pub fn x_window(&self, vx: i32) -> Option<Window> {
+        let (lo, hi) = if vx < 0 {
+            (-self.x_max as i64, -self.x_min as i64)
+        } else {
+            (self.x_min as i64, self.x_max as i64)
+        };
+        let v = (vx as i64).abs();
+        let stop = distance(v, v);
+
+        let first = first_step(1, v.max(1), |t| distance(v, t) >= lo)?;
+        let last = if stop <= hi {
+            None
+        } else {
+            Some(first_step(1, v, |t| distance(v, t) > hi)? - 1)
+        };
+
+        Window::new(first, last)
+    }
+
+    /// Steps during which the probe launched at vertical speed `vy` is level
+    /// with the target: once going up, once coming down.
+    pub fn y_windows(&self, vy: i32) -> Vec<Window> {
+        let v = vy as i64;
+        let (lo, hi) = (self.y_min as i64, self.y_max as i64);
+        let mut windows = Vec::new();
+
+        // the probe goes up for the first `v` steps
+        if v >= 1 {
+            let window = first_step(1, v, |t| height(v, t) >= lo).and_then(|first| {
+                let last = first_step(1, v, |t| height(v, t) > hi).map_or(v, |t| t - 1);
+                Window::new(first, Some(last))
+            });
+            windows.extend(window);
+        }
+
+        // then down, below the target once back to the launch height
+        let apex = v.max(0);
+        let end = 2 * apex + 2 + lo.abs();
+        let window = first_step(apex + 1, end, |t| height(v, t) <= hi).and_then(|first| {
+            let last = first_step(apex + 1, end, |t| height(v, t) < lo)? - 1;
+            Window::new(first, Some(last))
+        });
+        windows.extend(window);
+
+        windows
+    }
+
+    /// Every initial velocity hitting the target. Faster probes overshoot it
+    /// on the first step, or fall through it in one step. The windows of each
+    /// speed are only computed once, then matched against the other axis.
+    pub fn velocities(&self) -> Vec<(i32, i32)> {
+        let xs = (self.x_min.min(0)..=self.x_max.max(0))
+            .filter_map(|vx| Some((vx, self.x_window(vx)?)))
+            .collect::<Vec<_>>();
+        let ys = (self.y_min.min(0)..=self.y_max.max(-self.y_min - 1))
+            .map(|vy| (vy, self.y_windows(vy)))
+            .filter(|(_, windows)| !windows.is_empty())
+            .collect::<Vec<_>>();
+
+        xs.iter()
+            .flat_map(|&(vx, x)| {
+                ys.iter()
+                    .filter(move |(_, windows)| windows.iter().any(|&y| x.intersect(y).is_some()))
+                    .map(move |&(vy, _)| (vx, vy))
+            })
+            .collect()
+    }
+
+    /// Highest position reached by a probe hitting the target.
+    pub fn highest(&self) -> Option<i32> {
+        self.velocities()
+            .into_iter()
+            .map(|(_, vy)| vy.max(0) * (vy.max(0) + 1) / 2)
+            .max()
+    }
+}
+
+impl FromStr for Target {
+    type Err = TargetError;
+
+    /// `target area: x=20..30, y=-10..-5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || TargetError::Format(s.to_owned());
+        let range = |r: &str, axis: &str| -> Option<(i32, i32)> {
+            let (from, to) = r.trim().strip_prefix(axis)?.split_once("..")?;
+            Some((from.parse().ok()?, to.parse().ok()?))
+        };
+
+        let (x, y) = s
+            .trim()
+            .strip_prefix("target area:")
+            .and_then(|s| s.split_once(','))
+            .ok_or_else(error)?;
+        Target::new(
+            range(x, "x=").ok_or_else(error)?,
+            range(y, "y=").ok_or_else(error)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(target: &Target, (mut vx, mut vy): (i32, i32)) -> bool {
+        let (mut x, mut y) = (0, 0);
+        for _ in 0..1000 {
+            x += vx;
+            y += vy;
+            vx -= vx.signum();
+            vy -= 1;
+            if (target.x_min..=target.x_max).contains(&x)
+                && (target.y_min..=target.y_max).contains(&y)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_windows() {
+        let target = Target::new((20, 30), (-10, -5)).unwrap();
+        // reaches the target on step 5 and stops over it
+        assert_eq!(
+            target.x_window(6),
+            Some(Window {
+                first: 5,
+                last: None
+            })
+        );
+        assert_eq!(
+            target.x_window(7),
+            Some(Window {
+                first: 4,
+                last: None
+            })
+        );
+        assert_eq!(target.x_window(5), None);
+        assert_eq!(
+            target.x_window(11),
+            Some(Window {
+                first: 2,
+                last: Some(3)
+            })
+        );
+        // jumps over the target from 17 to 33
+        assert_eq!(target.x_window(17), None);
+        assert_eq!(
+            target.y_windows(9),
+            vec![Window {
+                first: 20,
+                last: Some(20)
+            }]
+        );
+        assert_eq!(
+            target.y_windows(-1),
+            vec![Window {
+                first: 3,
+                last: Some(4)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_against_simulation() {
+        for target in [
+            Target::new((20, 30), (-10, -5)).unwrap(),
+            Target::new((-30, -20), (-10, -5)).unwrap(),
+            Target::new((20, 30), (5, 10)).unwrap(),
+            Target::new((-3, 4), (2, 3)).unwrap(),
+        ] {
+            let velocities = target.velocities();
+            let expected = (-40..=40)
+                .flat_map(|vx| (-40..=40).map(move |vy| (vx, vy)))
+                .filter(|&v| simulate(&target, v))
+                .collect::<Vec<_>>();
+            assert_eq!(velocities, expected, "{target:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "target area: x=20..30, y=-10..-5".parse(),
+            Target::new((20, 30), (-10, -5))
+        );
+        assert_eq!(
+            "target area: x=20..30, y=-10..5".parse::<Target>(),
+            Err(TargetError::AtLaunchHeight)
+        );
+        assert!(matches!(
+            "x=20..30, y=-10..-5".parse::<Target>(),
+            Err(TargetError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportError {
+    Empty,
+    InvalidDigit {
+        line: usize,
+        digit: char,
+    },
+    /// A number hasn't as many bits as the first one.
+    InconsistentWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    TooWide(usize),
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Empty => write!(f, "empty report"),
+            ReportError::InvalidDigit { line, digit } => {
+                write!(f, "line {line}: invalid binary digit {digit:?}")
+            }
+            ReportError::InconsistentWidth {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: {found} bits where {expected} were expected"
+            ),
+            ReportError::TooWide(width) => write!(f, "{width} bits numbers don't fit in 64 bits"),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+/// Number of zeros and ones in a bit column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnStats {
+    pub zeros: usize,
+    pub ones: usize,
+}
+
+impl ColumnStats {
+    /// The most common bit, ties going to `tie`.
+    pub fn most_common(&self, tie: bool) -> bool {
+        match self.ones.cmp(&self.zeros) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => tie,
+        }
+    }
+
+    /// The least common bit, ties going to `tie`.
+    pub fn least_common(&self, tie: bool) -> bool {
+        match self.ones.cmp(&self.zeros) {
+            std::cmp::Ordering::Greater => false,
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Equal => tie,
+        }
+    }
+}
+
+/// Diagnostic report of binary numbers, all with the same width.
+#[derive(Debug)]
+pub struct Report {
+    width: usize,
+    numbers: Vec<u64>,
+}
+
+impl Report {
+    pub fn parse(input: &str) -> Result<Self, ReportError> {
+        let mut width = None;
+        let numbers = input
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                let line = i + 1;
+                let expected = *width.get_or_insert(l.len());
+                if l.len() != expected {
+                    return Err(ReportError::InconsistentWidth {
+                        line,
+                        expected,
+                        found: l.len(),
+                    });
+                }
+                if expected > 64 {
+                    return Err(ReportError::TooWide(expected));
+                }
+
+                l.chars().try_fold(0, |n, digit| match digit {
+                    '0' => Ok(n << 1),
+                    '1' => Ok(n << 1 | 1),
+                    _ => Err(ReportError::InvalidDigit { line, digit }),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match width {
+            None | Some(0) => Err(ReportError::Empty),
+            Some(width) => Ok(Report { width, numbers }),
+        }
+    }
+
+    /// Statistics of the column `col` of `numbers`, column 0 being the most
+    /// significant bit.
+    pub fn column(&self, numbers: &[u64], col: usize) -> ColumnStats {
+        let shift = self.width - 1 - col;
+        let ones = numbers.iter().filter(|&&n| (n >> shift) & 1 == 1).count();
+
+        ColumnStats {
+            zeros: numbers.len() - ones,
+            ones,
+        }
+    }
+
+    /// Statistics of every column, as many as the inferred width.
+    pub fn columns(&self) -> Vec<ColumnStats> {
+        (0..self.width)
+            .map(|col| self.column(&self.numbers, col))
+            .collect()
+    }
+
+    fn combine_columns(&self, bit: impl Fn(&ColumnStats) -> bool) -> u64 {
+        self.columns()
+            .iter()
+            .fold(0, |n, stats| n << 1 | bit(stats) as u64)
+    }
+
+    /// Most common bits, ties giving a 0.
+    pub fn gamma(&self) -> u64 {
+        self.combine_columns(|stats| stats.most_common(false))
+    }
+
+    /// Least common bits, ties giving a 1.
+    pub fn epsilon(&self) -> u64 {
+        self.combine_columns(|stats| stats.least_common(true))
+    }
+
+    /// Keep the numbers matching `bit` column after column until one is left.
+    /// A column where all remaining numbers agree doesn't filter anything.
+    fn rating(&self, bit: impl Fn(&ColumnStats) -> bool) -> u64 {
+        let mut numbers = self.numbers.clone();
+
+        for col in 0..self.width {
+            if numbers.len() == 1 {
+                break;
+            }
+            let stats = self.column(&numbers, col);
+            if stats.zeros == 0 || stats.ones == 0 {
+                continue;
+            }
+            let shift = self.width - 1 - col;
+            let bit = bit(&stats) as u64;
+            numbers.retain(|&n| (n >> shift) & 1 == bit);
+        }
+
+        // several numbers left means they are all equal
+        numbers[0]
+    }
+
+    pub fn oxygen(&self) -> u64 {
+        self.rating(|stats| stats.most_common(true))
+    }
+
+    pub fn co2(&self) -> u64 {
+        self.rating(|stats| stats.least_common(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns() {
+        let report = Report::parse("101\n001\n111\n100").unwrap();
+        assert_eq!(
+            report.columns(),
+            vec![
+                ColumnStats { zeros: 1, ones: 3 },
+                ColumnStats { zeros: 3, ones: 1 },
+                ColumnStats { zeros: 1, ones: 3 },
+            ]
+        );
+        assert_eq!(report.gamma(), 0b101);
+        assert_eq!(report.epsilon(), 0b010);
+    }
+
+    #[test]
+    fn test_ratings_with_duplicates() {
+        // every number starts with 1: the first column can't filter for CO2
+        let report = Report::parse("110\n110\n101").unwrap();
+        assert_eq!(report.oxygen(), 0b110);
+        assert_eq!(report.co2(), 0b101);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Report::parse("").unwrap_err(), ReportError::Empty);
+        assert_eq!(
+            Report::parse("101\n1010").unwrap_err(),
+            ReportError::InconsistentWidth {
+                line: 2,
+                expected: 3,
+                found: 4
+            }
+        );
+        assert_eq!(
+            Report::parse("101\n121").unwrap_err(),
+            ReportError::InvalidDigit {
+                line: 2,
+                digit: '2'
+            }
+        );
+        assert_eq!(
+            Report::parse(&"1".repeat(65)).unwrap_err(),
+            ReportError::TooWide(65)
+        );
+    }
+}
@@ -1,69 +1,62 @@
-const NR_BITS: usize = 12;
+mod diagnostic;
 
-fn count_bits_at(lines: &[&str], idx: usize) -> (usize, usize) {
-    let ones: usize = lines.iter().filter(|l| l.as_bytes()[idx] == b'1').count();
+use diagnostic::Report;
 
-    (lines.len() - ones, ones)
-}
-
-fn part1() -> u32 {
-    let input = include_str!("../input.txt");
-    let lines: Vec<&str> = input.lines().collect();
-
-    let mut gamma_rate = 0;
-    for i in 0..NR_BITS {
-        let (zero_count, one_count) = count_bits_at(&lines, i);
-        if one_count > zero_count {
-            gamma_rate |= 1 << (NR_BITS - 1 - i);
-        }
-    }
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-    gamma_rate * (!gamma_rate & ((1 << NR_BITS) - 1))
+fn part1(input: &str) -> Result<u64> {
+    let report = Report::parse(input)?;
+    Ok(report
+        .gamma()
+        .checked_mul(report.epsilon())
+        .ok_or("power consumption overflows")?)
 }
 
-fn part2() -> u32 {
-    let input = include_str!("../input.txt");
-
-    let rating = |most_common: bool| -> u32 {
-        let mut lines: Vec<_> = input.lines().collect();
-        for i in 0..NR_BITS {
-            let (zeroes, ones) = count_bits_at(&lines, i);
-            let filter_char = match (most_common, zeroes > ones) {
-                (true, true) | (false, false) => b'0',
-                _ => b'1',
-            };
-            lines = lines
-                .into_iter()
-                .filter(|l| l.as_bytes()[i] == filter_char)
-                .collect();
-
-            if lines.len() == 1 {
-                return u32::from_str_radix(lines.first().unwrap(), 2).unwrap();
-            }
-        }
-
-        panic!("failure to get rating");
-    };
-
-    let oxgygen_gen_rate = rating(true);
-    let co2_scrub_rate = rating(false);
-
-    oxgygen_gen_rate * co2_scrub_rate
+fn part2(input: &str) -> Result<u64> {
+    let report = Report::parse(input)?;
+    Ok(report
+        .oxygen()
+        .checked_mul(report.co2())
+        .ok_or("life support rating overflows")?)
 }
 
-fn main() {}
+fn main() -> Result<()> {
+    println!("part1={}", part1(include_str!("../input.txt"))?);
+    println!("part2={}", part2(include_str!("../input.txt"))?);
+    Ok(())
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    const TEST_INPUT: &str = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1());
+        assert_eq!(part1(TEST_INPUT).unwrap(), 198);
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2());
+        assert_eq!(part2(TEST_INPUT).unwrap(), 230);
+    }
+
+    #[test]
+    fn test_overflow() {
+        // 40 bits wide: each rate fits in a u64, not their product
+        let input = format!("1{}", "0".repeat(39));
+        assert!(part1(&input).is_err());
+        assert!(part2(&input).is_err());
     }
 }
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use utils::parse::{self, ParseError};
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    pub rows: usize,
+    pub columns: usize,
+    numbers: Vec<u32>,
+    marked: Vec<bool>,
+    // marked cells in each row, column and diagonal
+    row_marks: Vec<usize>,
+    col_marks: Vec<usize>,
+    diag_marks: [usize; 2],
+    unmarked_sum: u32,
+    won: bool,
+}
+
+/// A board completing a line. `draw` is the index of the winning number in
+/// the draw order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Win {
+    pub draw: usize,
+    pub number: u32,
+    pub board: usize,
+    pub score: u32,
+}
+
+/// Boards being played, where each number drawn is looked up in a table of
+/// the cells it appears in.
+#[derive(Debug)]
+pub struct Bingo {
+    boards: Vec<Board>,
+    cells: HashMap<u32, Vec<(usize, usize)>>,
+    diagonals: bool,
+    draws: usize,
+}
+
+impl Board {
+    pub fn parse(s: &str) -> parse::Result<Self> {
+        let grid = parse::lines(s, parse::words::<u32>)?;
+        let columns = grid.first().map_or(0, |row| row.len());
+        if let Some((line, row)) = grid.iter().enumerate().find(|(_, r)| r.len() != columns) {
+            return Err(ParseError::AtLine {
+                line: line + 1,
+                source: Box::new(ParseError::WrongCount {
+                    expected: columns,
+                    found: row.len(),
+                    input: s.lines().nth(line).unwrap_or_default().to_string(),
+                }),
+            });
+        }
+
+        let numbers = grid.concat();
+        Ok(Board {
+            rows: grid.len(),
+            columns,
+            marked: vec![false; numbers.len()],
+            row_marks: vec![0; grid.len()],
+            col_marks: vec![0; columns],
+            diag_marks: [0; 2],
+            unmarked_sum: numbers.iter().sum(),
+            numbers,
+            won: false,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn is_marked(&self, row: usize, col: usize) -> bool {
+        self.marked[row * self.columns + col]
+    }
+
+    /// Mark a cell, returning whether it completes a line. Diagonals only
+    /// exist on square boards.
+    fn mark(&mut self, cell: usize, diagonals: bool) -> bool {
+        if self.marked[cell] {
+            return false;
+        }
+        self.marked[cell] = true;
+        self.unmarked_sum -= self.numbers[cell];
+
+        let (row, col) = (cell / self.columns, cell % self.columns);
+        self.row_marks[row] += 1;
+        self.col_marks[col] += 1;
+        let mut bingo = self.row_marks[row] == self.columns || self.col_marks[col] == self.rows;
+
+        if diagonals && self.rows == self.columns {
+            let n = self.rows;
+            if row == col {
+                self.diag_marks[0] += 1;
+                bingo |= self.diag_marks[0] == n;
+            }
+            if row + col == n - 1 {
+                self.diag_marks[1] += 1;
+                bingo |= self.diag_marks[1] == n;
+            }
+        }
+
+        bingo
+    }
+}
+
+impl Bingo {
+    pub fn new(boards: Vec<Board>) -> Self {
+        let mut cells: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+        for (id, board) in boards.iter().enumerate() {
+            for (cell, &number) in board.numbers.iter().enumerate() {
+                cells.entry(number).or_default().push((id, cell));
+            }
+        }
+
+        Bingo {
+            boards,
+            cells,
+            diagonals: false,
+            draws: 0,
+        }
+    }
+
+    /// Also count diagonals as winning lines.
+    pub fn with_diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
+    /// The numbers to draw and the boards, separated by blank lines.
+    pub fn parse(input: &str) -> parse::Result<(Vec<u32>, Self)> {
+        let mut sections = parse::sections(input);
+        let draws = parse::list(sections.next().unwrap_or_default(), ',')?;
+        let boards = sections.map(Board::parse).collect::<parse::Result<_>>()?;
+
+        Ok((draws, Bingo::new(boards)))
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// Mark `number` on every board, returning the boards winning with it in
+    /// board order. A board only wins once.
+    pub fn draw(&mut self, number: u32) -> Vec<Win> {
+        let draw = self.draws;
+        self.draws += 1;
+
+        // mark every cell first, the number may appear several times on a
+        // board and all of them count as marked for the score
+        let mut completed = Vec::new();
+        for &(id, cell) in self.cells.get(&number).into_iter().flatten() {
+            if self.boards[id].mark(cell, self.diagonals) {
+                completed.push(id);
+            }
+        }
+        completed.sort_unstable();
+        completed.dedup();
+
+        let mut wins = Vec::new();
+        for id in completed {
+            let board = &mut self.boards[id];
+            if !board.won {
+                board.won = true;
+                wins.push(Win {
+                    draw,
+                    number,
+                    board: id,
+                    score: number * board.unmarked_sum,
+                });
+            }
+        }
+
+        wins
+    }
+
+    /// Draw all the numbers, returning every win in order.
+    pub fn play(&mut self, draws: &[u32]) -> Vec<Win> {
+        draws.iter().flat_map(|&number| self.draw(number)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular_board() {
+        let (draws, mut bingo) = Bingo::parse("3,1,6,5\n\n1 2 3\n4 5 6\n\n1 2\n3 4\n5 6").unwrap();
+        assert_eq!((bingo.boards()[1].rows, bingo.boards()[1].columns), (3, 2));
+
+        assert_eq!(
+            bingo.play(&draws),
+            vec![
+                // column 3 6 of the first board
+                Win {
+                    draw: 2,
+                    number: 6,
+                    board: 0,
+                    score: 6 * (2 + 4 + 5),
+                },
+                // row 5 6 of the second board
+                Win {
+                    draw: 3,
+                    number: 5,
+                    board: 1,
+                    score: 5 * (2 + 4),
+                },
+            ]
+        );
+        assert!(bingo.boards()[0].is_marked(1, 1));
+        assert!(!bingo.boards()[0].is_marked(0, 1));
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let input = "1,5,9\n\n1 2 3\n4 5 6\n7 8 9";
+        let (draws, mut bingo) = Bingo::parse(input).unwrap();
+        assert_eq!(bingo.play(&draws), vec![]);
+
+        let (draws, bingo) = Bingo::parse(input).unwrap();
+        let wins = bingo.with_diagonals(true).play(&draws);
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].score, 9 * (2 + 3 + 4 + 6 + 7 + 8));
+    }
+
+    #[test]
+    fn test_repeated_number() {
+        // both 1s are marked when the second column wins
+        let (draws, mut bingo) = Bingo::parse("5,1\n\n5 1\n2 1").unwrap();
+        assert_eq!(
+            bingo.play(&draws),
+            vec![Win {
+                draw: 1,
+                number: 1,
+                board: 0,
+                score: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_board() {
+        assert_eq!(
+            Board::parse("1 2 3\n4 5").unwrap_err(),
+            ParseError::AtLine {
+                line: 2,
+                source: Box::new(ParseError::WrongCount {
+                    expected: 3,
+                    found: 2,
+                    input: "4 5".to_string(),
+                }),
+            }
+        );
+    }
+}
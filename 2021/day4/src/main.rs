@@ -1,122 +1,77 @@
-#[derive(Clone, Copy, Debug, Default)]
-struct BingoNumber {
-    number: u32,
-    checked: bool,
-}
+mod bingo;
 
-#[derive(Clone, Copy, Debug, Default)]
-struct BingoBoard {
-    grid: [[BingoNumber; 5]; 5],
-    won: bool,
-}
+use bingo::Bingo;
 
-impl BingoBoard {
-    fn check_number(&mut self, number: u32) {
-        self.grid
-            .iter_mut()
-            .flatten()
-            .filter(|n| n.number == number)
-            .for_each(|n| n.checked = true);
-    }
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-    fn wins(&self) -> bool {
-        let row_bingo = |grid: &[[BingoNumber; 5]; 5]| -> bool {
-            grid.iter().any(|row| row.iter().all(|n| n.checked == true))
-        };
-        let column_bingo = |grid: &[[BingoNumber; 5]; 5]| -> bool {
-            (0..5).any(|col| grid[..][col].iter().all(|n| n.checked == true))
-        };
-        row_bingo(&self.grid) || column_bingo(&self.grid)
-    }
+fn first_win(input: &str, diagonals: bool) -> Result<u32> {
+    let (draws, bingo) = Bingo::parse(input)?;
+    let wins = bingo.with_diagonals(diagonals).play(&draws);
+    Ok(wins.first().ok_or("no winning board")?.score)
+}
 
-    fn score(&self) -> u32 {
-        self.grid
-            .iter()
-            .flatten()
-            .filter(|n| n.checked == false)
-            .map(|n| n.number)
-            .sum()
+fn last_win(input: &str, diagonals: bool) -> Result<u32> {
+    let (draws, bingo) = Bingo::parse(input)?;
+    let mut bingo = bingo.with_diagonals(diagonals);
+    let wins = bingo.play(&draws);
+    if wins.len() != bingo.boards().len() {
+        return Err("some boards never win".into());
     }
+    Ok(wins.last().ok_or("no winning board")?.score)
 }
 
-fn parse_input(input: &str) -> (Vec<u32>, Vec<BingoBoard>) {
-    let mut lines = input.split("\n\n");
-    let winning_numbers: Vec<_> = lines
-        .next()
-        .unwrap()
-        .split(",")
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
-
-    let boards = lines
-        .map(|l| {
-            let mut grid: [[BingoNumber; 5]; 5] = Default::default();
-
-            l.split_whitespace().enumerate().for_each(|(idx, val)| {
-                grid[idx / 5][idx % 5] = BingoNumber {
-                    number: val.parse::<u32>().unwrap(),
-                    checked: false,
-                }
-            });
-
-            BingoBoard { grid, won: false }
-        })
-        .collect();
-
-    (winning_numbers, boards)
+fn part1(input: &str) -> Result<u32> {
+    first_win(input, false)
 }
-fn part1() -> u32 {
-    let input = include_str!("../input.txt");
-    let (winning_numbers, mut boards) = parse_input(&input);
-
-    for wn in winning_numbers {
-        for board in boards.iter_mut() {
-            board.check_number(wn);
-
-            if board.wins() {
-                return wn * board.score();
-            }
-        }
-    }
 
-    panic!("no winning board");
+fn part2(input: &str) -> Result<u32> {
+    last_win(input, false)
 }
 
-fn part2() -> u32 {
-    let input = include_str!("../input.txt");
-    let (winning_numbers, mut boards) = parse_input(&input);
-
-    for wn in winning_numbers {
-        for board in boards.iter_mut() {
-            board.check_number(wn);
-
-            if board.wins() {
-                board.won = true;
-            }
-        }
-        if boards.len() == 1 && boards.first().unwrap().won == true {
-            return wn * boards.first().unwrap().score();
-        } else {
-            boards.retain(|b| b.won == false);
-        }
+fn main() -> Result<()> {
+    // cargo run -- --diagonals
+    if std::env::args().nth(1).as_deref() == Some("--diagonals") {
+        println!("part1={}", first_win(include_str!("../input.txt"), true)?);
+        println!("part2={}", last_win(include_str!("../input.txt"), true)?);
+        return Ok(());
     }
 
-    panic!("no winning board");
+    println!("part1={}", part1(include_str!("../input.txt"))?);
+    println!("part2={}", part2(include_str!("../input.txt"))?);
+    Ok(())
 }
 
-fn main() {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    const TEST_INPUT: &str =
+        "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1());
+        assert_eq!(part1(TEST_INPUT).unwrap(), 4512);
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2());
+        assert_eq!(part2(TEST_INPUT).unwrap(), 1924);
     }
 }
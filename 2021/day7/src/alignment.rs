@@ -0,0 +1,156 @@
+use std::num::ParseIntError;
+
+/// Fuel a crab burns to move by `distance`. The total fuel must be convex in
+/// the target position, which holds for any convex cost of the distance that
+/// never decreases as the distance grows. A cost like `(d - 5)²` breaks it and
+/// the search silently returns a wrong position.
+pub trait Cost {
+    fn fuel(&self, distance: u64) -> u64;
+
+    /// The lowest of the cheapest positions, by ternary search unless the
+    /// cost knows better.
+    fn optimum(&self, crabs: &Crabs) -> Option<i64> {
+        let (mut lo, mut hi) = (*crabs.positions.first()?, *crabs.positions.last()?);
+        let total = |position| crabs.fuel(position, self);
+
+        while hi - lo > 2 {
+            let third = (hi - lo) / 3;
+            let (m1, m2) = (lo + third, hi - third);
+            // the minimum can't be after m2 if it isn't cheaper than m1
+            if total(m1) <= total(m2) {
+                hi = m2;
+            } else {
+                lo = m1;
+            }
+        }
+
+        (lo..=hi).min_by_key(|&p| total(p))
+    }
+}
+
+impl<F: Fn(u64) -> u64> Cost for F {
+    fn fuel(&self, distance: u64) -> u64 {
+        self(distance)
+    }
+}
+
+/// One unit of fuel per step, cheapest at the median.
+pub struct Linear;
+
+impl Cost for Linear {
+    fn fuel(&self, distance: u64) -> u64 {
+        distance
+    }
+
+    fn optimum(&self, crabs: &Crabs) -> Option<i64> {
+        let n = crabs.positions.len();
+        (n > 0).then(|| crabs.positions[(n - 1) / 2])
+    }
+}
+
+/// One more unit of fuel for each step, cheapest within half a step of the
+/// mean: its floor or the integers next to it.
+pub struct Triangular;
+
+impl Cost for Triangular {
+    fn fuel(&self, distance: u64) -> u64 {
+        distance * (distance + 1) / 2
+    }
+
+    fn optimum(&self, crabs: &Crabs) -> Option<i64> {
+        let n = crabs.positions.len() as i64;
+        let mean = crabs.positions.iter().sum::<i64>().checked_div_euclid(n)?;
+
+        (mean - 1..=mean + 2).min_by_key(|&p| crabs.fuel(p, self))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment {
+    pub position: i64,
+    pub fuel: u64,
+}
+
+/// Horizontal positions of the crabs, sorted.
+#[derive(Debug)]
+pub struct Crabs {
+    positions: Vec<i64>,
+}
+
+impl Crabs {
+    /// `16,1,2,0,4`
+    pub fn parse(input: &str) -> Result<Self, ParseIntError> {
+        let mut positions = input
+            .trim()
+            .split(',')
+            .map(|x| x.trim().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        positions.sort_unstable();
+
+        Ok(Crabs { positions })
+    }
+
+    /// Fuel burnt by all the crabs to move to `position`.
+    pub fn fuel(&self, position: i64, cost: &(impl Cost + ?Sized)) -> u64 {
+        self.positions
+            .iter()
+            .map(|&p| cost.fuel(p.abs_diff(position)))
+            .sum()
+    }
+
+    /// The cheapest position to align the crabs on, `None` if there are none.
+    pub fn align(&self, cost: &impl Cost) -> Option<Alignment> {
+        let position = cost.optimum(self)?;
+
+        Some(Alignment {
+            position,
+            fuel: self.fuel(position, cost),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(crabs: &Crabs, cost: &impl Cost) -> Alignment {
+        let (first, last) = (crabs.positions[0], *crabs.positions.last().unwrap());
+        (first..=last)
+            .map(|position| Alignment {
+                position,
+                fuel: crabs.fuel(position, cost),
+            })
+            .min_by_key(|a| a.fuel)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        for input in ["16,1,2,0,4,2,7,1,2,14", "1,2", "-5,3,3,100,7", "4"] {
+            let crabs = Crabs::parse(input).unwrap();
+            assert_eq!(crabs.align(&Linear), Some(brute_force(&crabs, &Linear)));
+            assert_eq!(
+                crabs.align(&Triangular),
+                Some(brute_force(&crabs, &Triangular))
+            );
+            // the default ternary search, with costs of any steepness
+            let square = |d: u64| d * d;
+            assert_eq!(crabs.align(&square), Some(brute_force(&crabs, &square)));
+            let triangular = |d: u64| d * (d + 1) / 2;
+            assert_eq!(
+                crabs.align(&triangular),
+                crabs.align(&Triangular),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(Crabs::parse("").is_err());
+        let crabs = Crabs { positions: vec![] };
+        assert_eq!(crabs.align(&Linear), None);
+        assert_eq!(crabs.align(&Triangular), None);
+        assert_eq!(crabs.align(&|d| d), None);
+    }
+}
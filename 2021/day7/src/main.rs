@@ -1,47 +1,49 @@
-// optimal solution : https://www.reddit.com/r/adventofcode/comments/rar7ty/comment/hnk6gz0/
-fn parse_input() -> Vec<i32> {
-    include_str!("../input.txt")
-        .split(',')
-        .map(|x| x.trim().parse::<i32>().unwrap())
-        .collect()
-}
+mod alignment;
 
-fn fuel_cost(positions: &[i32], position: i32, cost_fn: fn(i32, i32) -> i32) -> i32 {
-    positions.iter().map(|&p| cost_fn(p, position)).sum()
-}
+use alignment::{Alignment, Crabs, Linear, Triangular};
 
-fn part1() -> i32 {
-    let mut positions = parse_input();
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-    let cost_fn = |x: i32, y: i32| (x - y).abs();
-    let len = positions.len();
-    // median
-    let (_, &mut best_position, _) = positions.select_nth_unstable(len / 2);
-    fuel_cost(&mut positions, best_position, cost_fn)
+fn part1(input: &str) -> Result<Alignment> {
+    Ok(Crabs::parse(input)?.align(&Linear).ok_or("no crabs")?)
 }
 
-fn part2() -> i32 {
-    let mut positions = parse_input();
-
-    let cost_fn = |x: i32, y: i32| (x - y).abs() * ((x - y).abs() + 1) / 2;
-    // mean: luck
-    let best_position = positions.iter().sum::<i32>() / positions.len() as i32;
-    fuel_cost(&mut positions, best_position, cost_fn)
+fn part2(input: &str) -> Result<Alignment> {
+    Ok(Crabs::parse(input)?.align(&Triangular).ok_or("no crabs")?)
 }
 
-fn main() {}
+fn main() -> Result<()> {
+    let Alignment { position, fuel } = part1(include_str!("../input.txt"))?;
+    println!("part1={fuel} (position {position})");
+    let Alignment { position, fuel } = part2(include_str!("../input.txt"))?;
+    println!("part2={fuel} (position {position})");
+    Ok(())
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    const TEST_INPUT: &str = "16,1,2,0,4,2,7,1,2,14";
 
     #[test]
     fn test_part1() {
-        println!("answer: {}", part1());
+        assert_eq!(
+            part1(TEST_INPUT).unwrap(),
+            Alignment {
+                position: 2,
+                fuel: 37
+            }
+        );
     }
 
     #[test]
     fn test_part2() {
-        println!("answer: {}", part2());
+        assert_eq!(
+            part2(TEST_INPUT).unwrap(),
+            Alignment {
+                position: 5,
+                fuel: 168
+            }
+        );
     }
 }
@@ -0,0 +1,360 @@
+use std::fmt;
+
+/// Lit segments or wires as a bitmask, bit 0 being `a`.
+type Mask = u8;
+
+fn segment(c: char) -> Option<Mask> {
+    matches!(c, 'a'..='h').then(|| 1 << (c as u8 - b'a'))
+}
+
+fn mask(s: &str) -> Option<Mask> {
+    s.chars().try_fold(0, |mask, c| Some(mask | segment(c)?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    InvalidSegment(char),
+    /// The digit lights the same segments as an earlier one
+    DuplicateDigit(usize),
+    NoDigits,
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::InvalidSegment(c) => write!(f, "invalid segment {c:?}"),
+            SpecError::DuplicateDigit(digit) => write!(f, "digit {digit} is a duplicate"),
+            SpecError::NoDigits => write!(f, "no digits"),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidLine(String),
+    InvalidPattern(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidLine(line) => write!(f, "invalid entry {line:?}"),
+            ParseError::InvalidPattern(pattern) => write!(f, "invalid pattern {pattern:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// No wiring shows every pattern as a digit
+    Inconsistent,
+    /// Wirings matching the patterns give different output values
+    Ambiguous(Vec<u64>),
+    /// The output value doesn't fit in a u64
+    Overflow,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Inconsistent => write!(f, "no wiring matches the entry"),
+            DecodeError::Ambiguous(values) => write!(f, "the output could be any of {values:?}"),
+            DecodeError::Overflow => write!(f, "the output value is too large"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// One line of notes: the unique signal patterns and the four digit output
+/// value, all on scrambled wires.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub patterns: Vec<Mask>,
+    pub output: Vec<Mask>,
+}
+
+impl Entry {
+    /// `be cfbegad ... | fdgacbe cefdb ...`
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let (patterns, output) = line
+            .split_once(" | ")
+            .ok_or_else(|| ParseError::InvalidLine(line.to_owned()))?;
+        let masks = |s: &str| {
+            s.split_ascii_whitespace()
+                .map(|p| mask(p).ok_or_else(|| ParseError::InvalidPattern(p.to_owned())))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Entry {
+            patterns: masks(patterns)?,
+            output: masks(output)?,
+        })
+    }
+}
+
+/// Which segments each digit lights, the digits being indices into `digits`.
+#[derive(Debug, Clone)]
+pub struct Display {
+    digits: Vec<Mask>,
+    segments: usize,
+}
+
+impl Display {
+    pub fn new(digits: &[&str]) -> Result<Self, SpecError> {
+        let mut masks = Vec::new();
+        for (digit, segments) in digits.iter().enumerate() {
+            let mask = segments.chars().try_fold(0, |mask, c| {
+                Ok(mask | segment(c).ok_or(SpecError::InvalidSegment(c))?)
+            })?;
+            if masks.contains(&mask) {
+                return Err(SpecError::DuplicateDigit(digit));
+            }
+            masks.push(mask);
+        }
+        let all = masks.iter().fold(0, |all, m| all | m);
+        if all == 0 {
+            return Err(SpecError::NoDigits);
+        }
+
+        Ok(Display {
+            digits: masks,
+            segments: 8 - all.leading_zeros() as usize,
+        })
+    }
+
+    /// The usual display, `a` on top then clockwise with `g` in the middle.
+    pub fn standard() -> Self {
+        Display::new(&[
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+        ])
+        .unwrap()
+    }
+
+    fn all(&self) -> Mask {
+        ((1u16 << self.segments) - 1) as Mask
+    }
+
+    /// The digit lighting exactly `segments`.
+    pub fn digit(&self, segments: Mask) -> Option<usize> {
+        self.digits.iter().position(|&d| d == segments)
+    }
+
+    /// Number of digits lighting `count` segments, a pattern of that many
+    /// wires being a known digit if there is only one.
+    pub fn digits_lighting(&self, count: u32) -> usize {
+        self.digits
+            .iter()
+            .filter(|d| d.count_ones() == count)
+            .count()
+    }
+
+    /// Segments each wire can be plugged to, narrowed down until nothing
+    /// changes: the wires of a pattern go to the segments of the digits it
+    /// can be, the other wires to the other segments, and a wire that can
+    /// only be plugged to one segment is the only one plugged to it. `None`
+    /// if some pattern can't be any digit.
+    fn propagate(&self, patterns: &[Mask]) -> Option<Vec<Mask>> {
+        let mut possible = vec![self.all(); self.segments];
+
+        loop {
+            let before = possible.clone();
+
+            for &pattern in patterns {
+                let (mut lit, mut unlit) = (0, 0);
+                for &digit in &self.digits {
+                    let fits = digit.count_ones() == pattern.count_ones()
+                        && (0..self.segments).all(|w| {
+                            let segments = if pattern & 1 << w != 0 { digit } else { !digit };
+                            possible[w] & segments != 0
+                        });
+                    if fits {
+                        lit |= digit;
+                        unlit |= !digit & self.all();
+                    }
+                }
+                for (w, segments) in possible.iter_mut().enumerate() {
+                    *segments &= if pattern & 1 << w != 0 { lit } else { unlit };
+                }
+            }
+
+            for w in 0..self.segments {
+                let segment = possible[w];
+                if segment.count_ones() == 1 {
+                    for (other, segments) in possible.iter_mut().enumerate() {
+                        if other != w {
+                            *segments &= !segment;
+                        }
+                    }
+                }
+            }
+
+            if possible.contains(&0) {
+                return None;
+            }
+            if possible == before {
+                return Some(possible);
+            }
+        }
+    }
+
+    /// Every wiring showing all the patterns of `entry` as digits, as the
+    /// segment of each wire.
+    pub fn wirings(&self, entry: &Entry) -> Vec<Vec<usize>> {
+        fn search(
+            display: &Display,
+            patterns: &[Mask],
+            possible: &[Mask],
+            wiring: &mut Vec<usize>,
+            used: Mask,
+            wirings: &mut Vec<Vec<usize>>,
+        ) {
+            let w = wiring.len();
+            if w == display.segments {
+                if patterns
+                    .iter()
+                    .all(|&p| display.digit(display.rewire(wiring, p)).is_some())
+                {
+                    wirings.push(wiring.clone());
+                }
+                return;
+            }
+
+            for segment in 0..display.segments {
+                let bit = 1 << segment;
+                if possible[w] & bit != 0 && used & bit == 0 {
+                    wiring.push(segment);
+                    search(display, patterns, possible, wiring, used | bit, wirings);
+                    wiring.pop();
+                }
+            }
+        }
+
+        let patterns = entry
+            .patterns
+            .iter()
+            .chain(&entry.output)
+            .copied()
+            .collect::<Vec<_>>();
+        if patterns.iter().any(|&p| p & !self.all() != 0) {
+            return Vec::new();
+        }
+        let Some(possible) = self.propagate(&patterns) else {
+            return Vec::new();
+        };
+
+        let mut wirings = Vec::new();
+        search(self, &patterns, &possible, &mut Vec::new(), 0, &mut wirings);
+        wirings
+    }
+
+    /// Segments lit by `wires` plugged as in `wiring`.
+    fn rewire(&self, wiring: &[usize], wires: Mask) -> Mask {
+        (0..self.segments)
+            .filter(|&w| wires & 1 << w != 0)
+            .fold(0, |mask, w| mask | 1 << wiring[w])
+    }
+
+    /// The output value of `entry`, its digits read in base the number of
+    /// digits.
+    pub fn decode(&self, entry: &Entry) -> Result<u64, DecodeError> {
+        let mut values = self
+            .wirings(entry)
+            .iter()
+            .map(|wiring| {
+                entry.output.iter().try_fold(0u64, |value, &wires| {
+                    let digit = self.digit(self.rewire(wiring, wires)).unwrap();
+                    value
+                        .checked_mul(self.digits.len() as u64)?
+                        .checked_add(digit as u64)
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(DecodeError::Overflow)?;
+        values.sort_unstable();
+        values.dedup();
+
+        match values[..] {
+            [] => Err(DecodeError::Inconsistent),
+            [value] => Ok(value),
+            _ => Err(DecodeError::Ambiguous(values)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+    #[test]
+    fn test_standard() {
+        let display = Display::standard();
+        let entry = Entry::parse(ENTRY).unwrap();
+        // the puzzle's example wiring: d to a, e to b, a to c and so on
+        assert_eq!(display.wirings(&entry), vec![vec![2, 5, 6, 0, 1, 3, 4]]);
+        assert_eq!(display.decode(&entry), Ok(5353));
+        assert_eq!(display.digits_lighting(2), 1);
+        assert_eq!(display.digits_lighting(5), 3);
+    }
+
+    #[test]
+    fn test_ambiguous_and_inconsistent() {
+        let display = Display::standard();
+        // only 1 and 7 are known: the output could be 1 or 7 but also the
+        // wires of 1 could be swapped
+        let entry = Entry::parse("ab dab | ab").unwrap();
+        assert_eq!(display.decode(&entry), Ok(1));
+        assert!(display.wirings(&entry).len() > 1);
+        let entry = Entry::parse("ab dab | dab ab").unwrap();
+        assert_eq!(display.decode(&entry), Ok(71));
+
+        // each five segment pattern could be a 2, a 3 or a 5
+        let entry = Entry::parse("abcde | abcdf").unwrap();
+        assert!(matches!(
+            display.decode(&entry),
+            Err(DecodeError::Ambiguous(_))
+        ));
+
+        // no digit lights a single segment
+        let entry = Entry::parse("ab | a").unwrap();
+        assert_eq!(display.decode(&entry), Err(DecodeError::Inconsistent));
+        // and the standard display has no eighth wire
+        let entry = Entry::parse("ab | abcdefgh").unwrap();
+        assert_eq!(display.decode(&entry), Err(DecodeError::Inconsistent));
+    }
+
+    #[test]
+    fn test_custom_display() {
+        // three digits on a vertical bar of three segments
+        let display = Display::new(&["a", "ab", "abc"]).unwrap();
+        let entry = Entry::parse("c bc abc | abc c").unwrap();
+        assert_eq!(display.decode(&entry), Ok(2 * 3));
+        assert_eq!(
+            Display::new(&["ab", "ba"]).unwrap_err(),
+            SpecError::DuplicateDigit(1)
+        );
+        assert_eq!(
+            Display::new(&["ax"]).unwrap_err(),
+            SpecError::InvalidSegment('x')
+        );
+        assert_eq!(Display::new(&[]).unwrap_err(), SpecError::NoDigits);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let display = Display::standard();
+        // twenty 1s still fit in a u64, a 7 before them doesn't
+        let ones = vec!["ab"; 20].join(" ");
+        let entry = Entry::parse(&format!("ab | {ones}")).unwrap();
+        assert_eq!(display.decode(&entry), Ok(11_111_111_111_111_111_111));
+        let entry = Entry::parse(&format!("ab dab | dab {ones}")).unwrap();
+        assert_eq!(display.decode(&entry), Err(DecodeError::Overflow));
+    }
+}
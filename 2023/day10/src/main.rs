@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use utils::geometry::{Point, Polygon};
 
 #[derive(Debug)]
 struct Grid {
@@ -23,10 +24,6 @@ impl Grid {
             None
         }
     }
-
-    fn set(&mut self, i: usize, j: usize, value: u8) {
-        self.tiles[j * self.max_i + i] = value;
-    }
 }
 
 fn parse(input: &str) -> Grid {
@@ -122,55 +119,23 @@ fn part1(input: &str) -> usize {
     path.len() / 2
 }
 
-// https://en.wikipedia.org/wiki/Point_in_polygon
-fn part2(input: &str, replace_start_with: u8) -> usize {
-    let mut grid = parse(input);
+fn part2(input: &str) -> usize {
+    let grid = parse(input);
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
     let path = loop_path(&grid, grid.start, &mut visited);
-    let path_set: HashSet<_> = path.iter().collect();
-
-    grid.set(grid.start.0, grid.start.1, replace_start_with);
-
-    let mut count = 0;
-    for j in 0..grid.max_j {
-        let mut i = 0;
-        let mut in_loop = false;
-
-        while i < grid.max_i {
-            if path_set.contains(&(i, j)) {
-                let c = grid.get(i, j).unwrap();
-
-                if c == b'|' {
-                    in_loop = !in_loop;
-                } else {
-                    i += 1;
-                    while grid.get(i, j).unwrap() == b'-' {
-                        i += 1;
-                    }
-                    let c_end = grid.get(i, j).unwrap();
-                    // we crossed a line (in -> out or out -> in)
-                    if (c, c_end) == (b'L', b'7') || (c, c_end) == (b'F', b'J') {
-                        in_loop = !in_loop;
-                    }
-                }
-                i += 1;
-            } else if in_loop {
-                while !path_set.contains(&(i, j)) {
-                    count += 1;
-                    i += 1;
-                }
-            } else {
-                i += 1;
-            }
-        }
-    }
-
-    count
+    let polygon = Polygon::new(
+        path.into_iter()
+            .map(|(i, j)| Point::new(i as i64, j as i64))
+            .collect(),
+    );
+
+    // shoelace + pick: tiles enclosed by the loop are its interior lattice points
+    polygon.interior_points() as usize
 }
 
 fn main() {
     println!("part1={}", part1(include_str!("../input.txt")));
-    println!("part2={}", part2(include_str!("../input.txt"), b'|'));
+    println!("part2={}", part2(include_str!("../input.txt")));
 }
 
 #[cfg(test)]
@@ -199,7 +164,7 @@ L--J.L7...LJS7F-7L7.
 .....|FJLJ|FJ|F7|.LJ
 ....FJL-7.||.||||...
 ....L---J.LJ.LJLJ...";
-        assert_eq!(part2(input, b'F'), 8);
+        assert_eq!(part2(input), 8);
     }
 
     #[test]
@@ -214,6 +179,6 @@ L---JF-JLJ.||-FJLJJ7
 7-L-JL7||F7|L7F-7F7|
 L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L";
-        assert_eq!(part2(input, b'7'), 10);
+        assert_eq!(part2(input), 10);
     }
 }
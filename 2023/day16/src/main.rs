@@ -1,193 +1,68 @@
-use rayon::prelude::*;
-use std::collections::HashSet;
+mod optics;
 
-#[derive(Debug)]
-struct Grid {
-    grid: Vec<u8>,
-    columns: usize,
-    rows: usize,
-}
+use optics::{Contraption, Direction};
 
-impl Grid {
-    fn get(&self, i: usize, j: usize) -> Option<u8> {
-        if i < self.columns && j < self.rows {
-            self.grid.get(j * self.columns + i).copied()
-        } else {
-            None
-        }
-    }
+fn parse(input: &str) -> Contraption {
+    Contraption::parse(input).expect("invalid contraption")
 }
 
-fn parse(input: &str) -> Grid {
-    let mut columns = 0;
-    let grid: Vec<_> = input
-        .lines()
-        .flat_map(|l| {
-            columns = l.len();
-            l.as_bytes()
-        })
-        .copied()
-        .collect();
-
-    let rows = grid.len() / columns;
-
-    Grid {
-        grid,
-        columns,
-        rows,
-    }
+fn part1(input: &str) -> usize {
+    parse(input).energize((0, 0), Direction::Right).count()
 }
 
-// Up => j + 1, Down => j - 1
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+fn part2(input: &str) -> usize {
+    let contraption = parse(input);
+    contraption
+        .entries()
+        .into_iter()
+        .map(|(pos, dir)| contraption.energize(pos, dir).count())
+        .max()
+        .unwrap_or(0)
 }
 
-fn dfs(
-    grid: &Grid,
-    visited: &mut HashSet<((usize, usize), Direction)>,
-    i: isize,
-    j: isize,
-    direction: Direction,
-) {
-    if i >= grid.columns as isize || j >= grid.rows as isize || i < 0 || j < 0 {
-        return;
-    }
-
-    if visited.contains(&((i as usize, j as usize), direction)) {
-        return;
-    }
-
-    if let Some(c) = grid.get(i as usize, j as usize) {
-        visited.insert(((i as usize, j as usize), direction));
-
-        match direction {
-            Direction::Up => match c {
-                b'.' | b'|' => dfs(grid, visited, i, j + 1, Direction::Up),
-                b'-' => {
-                    dfs(grid, visited, i + 1, j, Direction::Right);
-                    dfs(grid, visited, i - 1, j, Direction::Left);
-                }
-                b'\\' => dfs(grid, visited, i + 1, j, Direction::Right),
-                b'/' => dfs(grid, visited, i - 1, j, Direction::Left),
-                _ => unreachable!(),
-            },
-            Direction::Down => match c {
-                b'.' | b'|' => dfs(grid, visited, i, j - 1, Direction::Down),
-                b'-' => {
-                    dfs(grid, visited, i + 1, j, Direction::Right);
-                    dfs(grid, visited, i - 1, j, Direction::Left);
-                }
-                b'\\' => dfs(grid, visited, i - 1, j, Direction::Left),
-                b'/' => dfs(grid, visited, i + 1, j, Direction::Right),
-                _ => unreachable!(),
-            },
-            Direction::Left => match c {
-                b'.' | b'-' => dfs(grid, visited, i - 1, j, Direction::Left),
-                b'|' => {
-                    dfs(grid, visited, i, j + 1, Direction::Up);
-                    dfs(grid, visited, i, j - 1, Direction::Down);
-                }
-                b'\\' => dfs(grid, visited, i, j - 1, Direction::Down),
-                b'/' => dfs(grid, visited, i, j + 1, Direction::Up),
-                _ => unreachable!(),
-            },
-            Direction::Right => match c {
-                b'.' | b'-' => dfs(grid, visited, i + 1, j, Direction::Right),
-                b'|' => {
-                    dfs(grid, visited, i, j + 1, Direction::Up);
-                    dfs(grid, visited, i, j - 1, Direction::Down);
-                }
-                b'\\' => dfs(grid, visited, i, j + 1, Direction::Up),
-                b'/' => dfs(grid, visited, i, j - 1, Direction::Down),
-                _ => unreachable!(),
-            },
+/// The beam entering at `pos` towards `dir` drawn as in the puzzle: an arrow
+/// where it goes one way, the number of directions otherwise.
+fn render(input: &str, pos: (i32, i32), dir: Direction) -> String {
+    let energy = parse(input).energize(pos, dir);
+    let mut out = String::new();
+    for (row, line) in input.lines().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let directions = energy.directions((row as i32, col as i32));
+            out.push(match directions[..] {
+                _ if c != '.' => c,
+                [] => '.',
+                [Direction::Up] => '^',
+                [Direction::Right] => '>',
+                [Direction::Down] => 'v',
+                [Direction::Left] => '<',
+                _ => char::from_digit(directions.len() as u32, 10).unwrap(),
+            });
         }
+        out.push('\n');
     }
-}
 
-fn part1(input: &str) -> usize {
-    let grid = parse(input);
-    let mut visited = HashSet::new();
-
-    dfs(&grid, &mut visited, 0, 0, Direction::Right);
-    visited
-        .iter()
-        .map(|&(coord, _)| coord)
-        .collect::<HashSet<_>>()
-        .len()
+    out
 }
 
-fn part2(input: &str) -> usize {
-    let grid = parse(input);
-    let mut max_a = 0;
-    let mut max_b = 0;
-
-    for (j, direction) in [
-        (0, Direction::Right),
-        (0, Direction::Left),
-        (0, Direction::Up),
-        (grid.rows - 1, Direction::Right),
-        (grid.rows - 1, Direction::Left),
-        (grid.rows - 1, Direction::Down),
-    ]
-    .into_iter()
-    {
-        let max = (0..grid.columns)
-            .into_par_iter()
-            .map(|i| {
-                let mut visited = HashSet::new();
-                dfs(&grid, &mut visited, i as isize, j as isize, direction);
-                visited
-                    .iter()
-                    .map(|&(coord, _)| coord)
-                    .collect::<HashSet<_>>()
-                    .len()
-            })
-            .max()
-            .unwrap();
-
-        if max > max_a {
-            max_a = max;
-        }
-    }
-    for (i, direction) in [
-        (0, Direction::Right),
-        (0, Direction::Down),
-        (0, Direction::Up),
-        (grid.columns - 1, Direction::Left),
-        (grid.columns - 1, Direction::Down),
-        (grid.columns - 1, Direction::Up),
-    ]
-    .into_iter()
-    {
-        let max = (0..grid.rows)
-            .into_par_iter()
-            .map(|j| {
-                let mut visited = HashSet::new();
-                dfs(&grid, &mut visited, i as isize, j as isize, direction);
-                visited
-                    .iter()
-                    .map(|&(coord, _)| coord)
-                    .collect::<HashSet<_>>()
-                    .len()
-            })
-            .max()
-            .unwrap();
-
-        if max > max_b {
-            max_b = max;
+fn main() {
+    // cargo run -- --beam 0,3 down
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, pos, dir] = &args[..] {
+        if flag == "--beam" {
+            let (row, col) = pos.split_once(',').expect("expected row,col");
+            let dir = match dir.as_str() {
+                "up" => Direction::Up,
+                "right" => Direction::Right,
+                "down" => Direction::Down,
+                "left" => Direction::Left,
+                _ => panic!("expected up, right, down or left"),
+            };
+            let pos = (row.parse().unwrap(), col.parse().unwrap());
+            print!("{}", render(include_str!("../input.txt"), pos, dir));
+            return;
         }
     }
 
-    max_a.max(max_b)
-}
-
-fn main() {
     println!("part1={}", part1(include_str!("../input.txt")));
     println!("part2={}", part2(include_str!("../input.txt")));
 }
@@ -215,4 +90,14 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(TEST_INPUT), 51);
     }
+
+    #[test]
+    fn test_render() {
+        // the beam of the puzzle's description
+        let beam = render(TEST_INPUT, (0, 0), Direction::Right);
+        let lines = beam.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], r">|<<<\....");
+        assert_eq!(lines[3], r".v...v^.|.");
+        assert_eq!(lines[6], r".v../2\\..");
+    }
 }
@@ -0,0 +1,438 @@
+use std::fmt;
+
+use utils::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Element {
+    Empty,
+    /// `/`
+    Mirror,
+    /// `\`
+    BackMirror,
+    /// `|`
+    VerticalSplitter,
+    /// `-`
+    HorizontalSplitter,
+}
+
+impl Element {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Element::Empty),
+            '/' => Some(Element::Mirror),
+            '\\' => Some(Element::BackMirror),
+            '|' => Some(Element::VerticalSplitter),
+            '-' => Some(Element::HorizontalSplitter),
+            _ => None,
+        }
+    }
+
+    /// Directions a beam travelling towards `dir` leaves the element in.
+    pub fn exits(self, dir: Direction) -> &'static [Direction] {
+        use Direction::*;
+        // indexed by element, then by the incoming direction: up, right, down, left
+        const EXITS: [[&[Direction]; 4]; 5] = [
+            [&[Up], &[Right], &[Down], &[Left]],
+            [&[Right], &[Up], &[Left], &[Down]],
+            [&[Left], &[Down], &[Right], &[Up]],
+            [&[Up], &[Up, Down], &[Down], &[Up, Down]],
+            [&[Left, Right], &[Right], &[Left, Right], &[Left]],
+        ];
+
+        EXITS[self as usize][dir as usize]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownElement { row: i32, col: i32, element: char },
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownElement { row, col, element } => {
+                write!(f, "unknown element {element:?} at {row},{col}")
+            }
+            ParseError::Empty => write!(f, "empty contraption"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type Pos = (i32, i32);
+
+/// A beam entering a cell towards a direction, indexed as
+/// `cell * 4 + direction`.
+type State = usize;
+
+/// Set of states, one bit each.
+#[derive(Clone, Debug)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn new(len: usize) -> Self {
+        Bits(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, state: State) {
+        self.0[state / 64] |= 1 << (state % 64);
+    }
+
+    fn contains(&self, state: State) -> bool {
+        self.0[state / 64] & (1 << (state % 64)) != 0
+    }
+
+    fn union(&mut self, other: &Bits) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a |= b;
+        }
+    }
+}
+
+/// The straight run of a beam leaving a splitter, or entering from the edge,
+/// through mirrors and the pointy ends of splitters.
+#[derive(Debug)]
+struct Segment {
+    states: Vec<State>,
+    /// The splitter the beam is split by at the end, if it doesn't leave.
+    end: Option<usize>,
+}
+
+/// The contraption, with the states reachable from every splitter computed
+/// once so that each entry point only has to trace its first segment.
+#[derive(Debug)]
+pub struct Contraption {
+    rows: i32,
+    columns: i32,
+    elements: Vec<Element>,
+    /// Splitter index of each cell
+    splitters: Vec<Option<usize>>,
+    /// Component of each splitter: splitters feeding each other in a loop
+    /// reach the same states.
+    reach: Vec<usize>,
+    /// Reachable states of each component
+    components: Vec<Bits>,
+}
+
+/// The cells a beam goes through, with the directions it goes through them in.
+#[derive(Debug)]
+pub struct Energy {
+    rows: i32,
+    columns: i32,
+    states: Bits,
+}
+
+impl Contraption {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        if input.trim().is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let grid = Grid::<char>::parse(input);
+        let elements = grid
+            .position_iter()
+            .map(|(row, col)| {
+                let element = grid.get(row, col).unwrap();
+                Element::from_char(element).ok_or(ParseError::UnknownElement { row, col, element })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut splitters = vec![None; elements.len()];
+        let mut count = 0;
+        for (cell, element) in elements.iter().enumerate() {
+            if matches!(
+                element,
+                Element::VerticalSplitter | Element::HorizontalSplitter
+            ) {
+                splitters[cell] = Some(count);
+                count += 1;
+            }
+        }
+
+        let mut contraption = Contraption {
+            rows: grid.rows,
+            columns: grid.columns,
+            elements,
+            splitters,
+            reach: Vec::new(),
+            components: Vec::new(),
+        };
+        contraption.link();
+
+        Ok(contraption)
+    }
+
+    fn state(&self, (row, col): Pos, dir: Direction) -> Option<State> {
+        (row >= 0 && row < self.rows && col >= 0 && col < self.columns)
+            .then(|| (row * self.columns + col) as usize * 4 + dir as usize)
+    }
+
+    fn step(&self, cell: usize, dir: Direction) -> Option<State> {
+        let (dr, dc) = dir.delta();
+        let (row, col) = (cell as i32 / self.columns, cell as i32 % self.columns);
+        self.state((row + dr, col + dc), dir)
+    }
+
+    fn trace(&self, start: Option<State>) -> Segment {
+        let mut states = Vec::new();
+        let mut state = start;
+
+        while let Some(current) = state {
+            states.push(current);
+            let cell = current / 4;
+            match self.elements[cell].exits(Direction::ALL[current % 4]) {
+                &[dir] => state = self.step(cell, dir),
+                _ => {
+                    return Segment {
+                        states,
+                        end: self.splitters[cell],
+                    }
+                }
+            }
+            // mirrors can't merge two beams, so a loop goes back to the start
+            if state == start {
+                break;
+            }
+        }
+
+        Segment { states, end: None }
+    }
+
+    /// Both segments leaving each splitter, whichever side it is hit on.
+    fn outgoing(&self) -> Vec<[Segment; 2]> {
+        let mut outgoing = Vec::new();
+        for (cell, element) in self.elements.iter().enumerate() {
+            if self.splitters[cell].is_some() {
+                let [a, b] = match element {
+                    Element::VerticalSplitter => [Direction::Up, Direction::Down],
+                    _ => [Direction::Left, Direction::Right],
+                };
+                outgoing.push([
+                    self.trace(self.step(cell, a)),
+                    self.trace(self.step(cell, b)),
+                ]);
+            }
+        }
+
+        outgoing
+    }
+
+    /// Tarjan's algorithm over the splitters, with an explicit stack. The
+    /// components come out successors first, so their reachable states are
+    /// computed as soon as they are found.
+    fn link(&mut self) {
+        let outgoing = self.outgoing();
+        let n = outgoing.len();
+        let states = self.elements.len() * 4;
+        let next = |v: usize| outgoing[v].iter().filter_map(|s| s.end);
+
+        let mut index = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut counter = 0;
+        self.reach = vec![usize::MAX; n];
+
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            let mut work = vec![(root, 0)];
+            index[root] = counter;
+            low[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (v, ref mut edge)) = work.last_mut() {
+                if let Some(w) = next(v).nth(*edge) {
+                    *edge += 1;
+                    if index[w] == usize::MAX {
+                        index[w] = counter;
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        work.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some(&(u, _)) = work.last() {
+                    low[u] = low[u].min(low[v]);
+                }
+                if low[v] != index[v] {
+                    continue;
+                }
+
+                let component = self.components.len();
+                let mut members = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    self.reach[w] = component;
+                    members.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+
+                let mut bits = Bits::new(states);
+                for &w in &members {
+                    for segment in &outgoing[w] {
+                        segment.states.iter().for_each(|&s| bits.insert(s));
+                        if let Some(end) = segment.end {
+                            // the component itself is still being built
+                            if self.reach[end] != component {
+                                bits.union(&self.components[self.reach[end]]);
+                            }
+                        }
+                    }
+                }
+                self.components.push(bits);
+            }
+        }
+    }
+
+    /// Every edge cell with the direction pointing into the contraption.
+    pub fn entries(&self) -> Vec<(Pos, Direction)> {
+        let (last_row, last_col) = (self.rows - 1, self.columns - 1);
+        let rows = (0..self.rows).flat_map(|row| {
+            [
+                ((row, 0), Direction::Right),
+                ((row, last_col), Direction::Left),
+            ]
+        });
+        let columns = (0..self.columns).flat_map(|col| {
+            [
+                ((0, col), Direction::Down),
+                ((last_row, col), Direction::Up),
+            ]
+        });
+
+        rows.chain(columns).collect()
+    }
+
+    /// The beam entering at `pos` towards `dir`.
+    pub fn energize(&self, pos: Pos, dir: Direction) -> Energy {
+        let segment = self.trace(self.state(pos, dir));
+        let mut states = Bits::new(self.elements.len() * 4);
+        segment.states.iter().for_each(|&s| states.insert(s));
+        if let Some(end) = segment.end {
+            states.union(&self.components[self.reach[end]]);
+        }
+
+        Energy {
+            rows: self.rows,
+            columns: self.columns,
+            states,
+        }
+    }
+}
+
+impl Energy {
+    fn cell(&self, (row, col): Pos) -> Option<usize> {
+        (row >= 0 && row < self.rows && col >= 0 && col < self.columns)
+            .then(|| (row * self.columns + col) as usize)
+    }
+
+    /// Directions the beam enters `pos` in.
+    pub fn directions(&self, pos: Pos) -> Vec<Direction> {
+        let Some(cell) = self.cell(pos) else {
+            return Vec::new();
+        };
+        Direction::ALL
+            .into_iter()
+            .filter(|&dir| self.states.contains(cell * 4 + dir as usize))
+            .collect()
+    }
+
+    /// Number of energized cells.
+    pub fn count(&self) -> usize {
+        self.states
+            .0
+            .iter()
+            .map(|&word| {
+                // fold the four direction bits of each cell onto its first one
+                let any = word | word >> 1 | word >> 2 | word >> 3;
+                (any & 0x1111_1111_1111_1111).count_ones() as usize
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exits() {
+        use Direction::*;
+        assert_eq!(Element::Mirror.exits(Up), &[Right]);
+        assert_eq!(Element::BackMirror.exits(Up), &[Left]);
+        assert_eq!(Element::VerticalSplitter.exits(Down), &[Down]);
+        assert_eq!(Element::HorizontalSplitter.exits(Down), &[Left, Right]);
+    }
+
+    #[test]
+    fn test_loops() {
+        // the splitter feeds itself through the mirrors
+        let contraption = Contraption::parse("/.\\\n...\n|./").unwrap();
+        let energy = contraption.energize((2, 1), Direction::Left);
+        assert_eq!(energy.count(), 8);
+        assert_eq!(energy.directions((2, 0)), vec![Direction::Left]);
+        assert_eq!(energy.directions((1, 0)), vec![Direction::Up]);
+        assert_eq!(energy.directions((1, 1)), vec![]);
+        assert_eq!(energy.directions((5, 5)), vec![]);
+
+        // a loop of mirrors only, entered through the pointy end of a splitter
+        let contraption = Contraption::parse("/.\\\n|..\n\\./").unwrap();
+        let energy = contraption.energize((1, 0), Direction::Up);
+        assert_eq!(energy.count(), 8);
+        assert_eq!(energy.directions((1, 0)), vec![Direction::Up]);
+        assert_eq!(contraption.entries().len(), 12);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Contraption::parse("..\n.#").unwrap_err(),
+            ParseError::UnknownElement {
+                row: 1,
+                col: 1,
+                element: '#'
+            }
+        );
+        assert_eq!(Contraption::parse("").unwrap_err(), ParseError::Empty);
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use utils::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Direction::ALL[(self as usize + 3) % 4]
+    }
+
+    fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+/// The direction of the straight run a crucible is on, and how many blocks
+/// it has moved in it. `None` before its first move.
+pub type Heading = Option<(Direction, u32)>;
+
+/// How a crucible may move.
+pub trait Movement {
+    /// Whether the crucible can move one block towards `dir`.
+    fn allows(&self, heading: Heading, dir: Direction) -> bool;
+
+    /// Whether the crucible can stop at the end of its run.
+    fn can_stop(&self, _heading: Heading) -> bool {
+        true
+    }
+}
+
+/// A crucible going straight for `min_run` to `max_run` blocks, then turning
+/// in the directions it is allowed to.
+#[derive(Clone, Copy, Debug)]
+pub struct Crucible {
+    pub min_run: u32,
+    pub max_run: u32,
+    pub left: bool,
+    pub right: bool,
+    pub reverse: bool,
+}
+
+impl Crucible {
+    pub const REGULAR: Crucible = Crucible::new(1, 3);
+    pub const ULTRA: Crucible = Crucible::new(4, 10);
+
+    /// A crucible turning either way but never reversing.
+    pub const fn new(min_run: u32, max_run: u32) -> Self {
+        Crucible {
+            min_run,
+            max_run,
+            left: true,
+            right: true,
+            reverse: false,
+        }
+    }
+}
+
+impl Movement for Crucible {
+    fn allows(&self, heading: Heading, dir: Direction) -> bool {
+        let Some((current, run)) = heading else {
+            return true;
+        };
+        if dir == current {
+            return run < self.max_run;
+        }
+
+        run >= self.min_run
+            && if dir == current.turn_left() {
+                self.left
+            } else if dir == current.turn_right() {
+                self.right
+            } else {
+                self.reverse
+            }
+    }
+
+    fn can_stop(&self, heading: Heading) -> bool {
+        heading.is_none_or(|(_, run)| run >= self.min_run)
+    }
+}
+
+type Pos = (i32, i32);
+
+/// The city blocks with the heat lost entering each of them.
+#[derive(Debug)]
+pub struct City {
+    blocks: Grid<u8>,
+}
+
+/// A cheapest route, from one of the starts to one of the goals.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Path {
+    pub heat: usize,
+    /// Every block of the route, starting block included
+    pub blocks: Vec<Pos>,
+}
+
+impl City {
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut blocks = Grid::<u8>::parse(input);
+        for block in &mut blocks.grid {
+            *block = (*block as char).to_digit(10)? as u8;
+        }
+
+        Some(City { blocks })
+    }
+
+    pub fn top_left(&self) -> Pos {
+        (0, 0)
+    }
+
+    pub fn bottom_right(&self) -> Pos {
+        (self.blocks.rows - 1, self.blocks.columns - 1)
+    }
+
+    /// Dijkstra over blocks and headings, from any of `starts` to any of
+    /// `goals`. `None` if no goal can be reached.
+    pub fn solve(&self, starts: &[Pos], goals: &[Pos], movement: &impl Movement) -> Option<Path> {
+        type State = (Pos, Heading);
+
+        let mut heats = HashMap::new();
+        let mut preds: HashMap<State, State> = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        for &start in starts {
+            if self.blocks.get(start.0, start.1).is_some() {
+                heats.insert((start, None), 0);
+                frontier.push(Reverse((0, (start, None))));
+            }
+        }
+
+        while let Some(Reverse((heat, state))) = frontier.pop() {
+            let (pos, heading) = state;
+            if heats.get(&state).is_some_and(|&h| h < heat) {
+                continue;
+            }
+            if goals.contains(&pos) && movement.can_stop(heading) {
+                let mut blocks = vec![pos];
+                let mut current = state;
+                while let Some(&pred) = preds.get(&current) {
+                    blocks.push(pred.0);
+                    current = pred;
+                }
+                blocks.reverse();
+
+                return Some(Path { heat, blocks });
+            }
+
+            for dir in Direction::ALL {
+                if !movement.allows(heading, dir) {
+                    continue;
+                }
+                let (dr, dc) = dir.delta();
+                let next_pos = (pos.0 + dr, pos.1 + dc);
+                let Some(loss) = self.blocks.get(next_pos.0, next_pos.1) else {
+                    continue;
+                };
+                let run = match heading {
+                    Some((current, run)) if current == dir => run + 1,
+                    _ => 1,
+                };
+                let next = (next_pos, Some((dir, run)));
+                let next_heat = heat + loss as usize;
+                if heats.get(&next).is_none_or(|&h| next_heat < h) {
+                    heats.insert(next, next_heat);
+                    preds.insert(next, state);
+                    frontier.push(Reverse((next_heat, next)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The city with the blocks of `path` after the first drawn as the
+    /// direction the crucible entered them in.
+    pub fn render(&self, path: &Path) -> String {
+        let mut arrows = HashMap::new();
+        for pair in path.blocks.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let dir = Direction::ALL
+                .into_iter()
+                .find(|d| d.delta() == (to.0 - from.0, to.1 - from.1))
+                .unwrap();
+            arrows.insert(to, dir.arrow());
+        }
+
+        let mut out = String::new();
+        for row in 0..self.blocks.rows {
+            for col in 0..self.blocks.columns {
+                let loss = self.blocks.get(row, col).unwrap();
+                out.push(
+                    arrows
+                        .get(&(row, col))
+                        .copied()
+                        .unwrap_or((b'0' + loss) as char),
+                );
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CITY: &str = "1911\n1919\n1111";
+
+    #[test]
+    fn test_starts_and_goals() {
+        let city = City::parse(CITY).unwrap();
+        let path = city
+            .solve(&[(0, 0), (0, 3)], &[(2, 0), (2, 3)], &Crucible::REGULAR)
+            .unwrap();
+        assert_eq!(path.heat, 2);
+        assert!(path.blocks == [(0, 0), (1, 0), (2, 0)] || path.blocks == [(0, 3), (1, 3), (2, 3)]);
+
+        let path = city
+            .solve(&[(1, 1)], &[(1, 1)], &Crucible::REGULAR)
+            .unwrap();
+        assert_eq!(
+            path,
+            Path {
+                heat: 0,
+                blocks: vec![(1, 1)]
+            }
+        );
+
+        assert!(city
+            .solve(&[(0, 0)], &[(5, 5)], &Crucible::REGULAR)
+            .is_none());
+        // can't stop before going 4 blocks straight
+        assert!(city.solve(&[(0, 0)], &[(0, 2)], &Crucible::ULTRA).is_none());
+    }
+
+    #[test]
+    fn test_turns() {
+        let city = City::parse(CITY).unwrap();
+        let right_only = Crucible {
+            left: false,
+            ..Crucible::new(1, 3)
+        };
+        // the cheap way along the bottom row needs a left turn
+        let path = city.solve(&[(2, 0)], &[(0, 2)], &right_only).unwrap();
+        assert_eq!(path.blocks, vec![(2, 0), (1, 0), (0, 0), (0, 1), (0, 2)]);
+        assert_eq!(path.heat, 12);
+
+        let path = city
+            .solve(&[(2, 0)], &[(0, 2)], &Crucible::new(1, 3))
+            .unwrap();
+        assert_eq!(path.heat, 4);
+    }
+}
@@ -1,92 +1,50 @@
-#[derive(Clone, Copy, Debug)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-type Polygon = Vec<Point>;
-type Perimeter = usize;
+use utils::geometry::{Direction, Point, Polygon};
 
-fn parse(input: &str, part2: bool) -> (Polygon, Perimeter) {
-    fn next_point(current: Point, direction: char, distance: isize) -> Point {
-        match direction {
-            'R' => Point {
-                x: current.x + distance,
-                y: current.y,
-            },
-            'L' => Point {
-                x: current.x - distance,
-                y: current.y,
-            },
-            'U' => Point {
-                x: current.x,
-                y: current.y - distance,
-            },
-            'D' => Point {
-                x: current.x,
-                y: current.y + distance,
-            },
+fn parse(input: &str, part2: bool) -> Polygon {
+    let moves = input.lines().map(|l| {
+        let mut it = l.split_whitespace();
+        match (it.next(), it.next(), it.next()) {
+            (Some(direction), Some(distance), Some(rgb)) => {
+                let (direction, distance) = if !part2 {
+                    (
+                        direction.chars().next().unwrap(),
+                        distance.parse::<i64>().unwrap(),
+                    )
+                } else {
+                    let direction = match rgb.chars().nth(rgb.len() - 2).unwrap() {
+                        '0' => 'R',
+                        '1' => 'D',
+                        '2' => 'L',
+                        '3' => 'U',
+                        _ => unreachable!(),
+                    };
+                    let distance = i64::from_str_radix(&rgb[2..rgb.len() - 2], 16).unwrap();
+                    (direction, distance)
+                };
+                let direction = match direction {
+                    'R' => Direction::Right,
+                    'L' => Direction::Left,
+                    'U' => Direction::Up,
+                    'D' => Direction::Down,
+                    _ => unreachable!(),
+                };
+                (direction, distance)
+            }
             _ => unreachable!(),
         }
-    }
-
-    let mut p = Point { x: 0, y: 0 };
-    let mut perimeter = 0;
-    let polygon = input
-        .lines()
-        .map(|l| {
-            let mut it = l.split_whitespace();
-            match (it.next(), it.next(), it.next()) {
-                (Some(direction), Some(distance), Some(rgb)) => {
-                    if !part2 {
-                        let direction = direction.chars().next().unwrap();
-                        let distance = distance.parse::<isize>().unwrap();
-                        perimeter += distance;
-                        p = next_point(p, direction, distance);
-                        p
-                    } else {
-                        let direction = match rgb.chars().nth(rgb.len() - 2).unwrap() {
-                            '0' => 'R',
-                            '1' => 'D',
-                            '2' => 'L',
-                            '3' => 'U',
-                            _ => unreachable!(),
-                        };
-                        let distance = isize::from_str_radix(&rgb[2..rgb.len() - 2], 16).unwrap();
-                        perimeter += distance;
-                        p = next_point(p, direction, distance);
-                        p
-                    }
-                }
-                _ => unreachable!(),
-            }
-        })
-        .collect::<Vec<_>>();
-
-    (polygon, perimeter as usize)
-}
-
-fn inner_area(polygon: &Polygon) -> usize {
-    let n = polygon.len() as isize;
-    let area = (0..n - 1)
-        .map(|i| {
-            polygon[i as usize].x * polygon[(i + 1) as usize].y
-                - polygon[i as usize].y * polygon[(i + 1) as usize].x
-        })
-        .sum::<isize>();
+    });
 
-    area as usize
+    Polygon::from_moves(Point::new(0, 0), moves)
 }
 
 fn part1(input: &str) -> usize {
-    let (polygons, perimeter) = parse(input, false);
     // shoelace + pick
-    (inner_area(&polygons) + perimeter) / 2 + 1
+    parse(input, false).lattice_points() as usize
 }
 
 fn part2(input: &str) -> usize {
-    let (polygons, perimeter) = parse(input, true);
     // shoelace + pick
-    (inner_area(&polygons) + perimeter) / 2 + 1
+    parse(input, true).lattice_points() as usize
 }
 
 fn main() {
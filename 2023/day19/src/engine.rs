@@ -0,0 +1,395 @@
+use std::{collections::HashMap, fmt, ops::RangeInclusive};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cmp {
+    Lt,
+    Gt,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Condition {
+    pub attr: String,
+    pub cmp: Cmp,
+    pub value: u64,
+}
+
+/// A rule as written in the input, e.g. `a<2006:qkq` or `rfg`.
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    pub condition: Option<Condition>,
+    pub target: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<Rule>,
+}
+
+impl From<&str> for Rule {
+    fn from(s: &str) -> Self {
+        match s.split_once(':') {
+            None => Rule {
+                condition: None,
+                target: s.to_owned(),
+            },
+            Some((condition, target)) => {
+                let pos_op = condition.find(['<', '>']).unwrap();
+                let cmp = match &condition[pos_op..pos_op + 1] {
+                    "<" => Cmp::Lt,
+                    _ => Cmp::Gt,
+                };
+                Rule {
+                    condition: Some(Condition {
+                        attr: condition[..pos_op].to_owned(),
+                        cmp,
+                        value: condition[pos_op + 1..].parse().unwrap(),
+                    }),
+                    target: target.to_owned(),
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for Workflow {
+    fn from(s: &str) -> Self {
+        let (name, rules) = s.split_once('{').unwrap();
+        Workflow {
+            name: name.to_owned(),
+            rules: rules
+                .trim_end_matches('}')
+                .split(',')
+                .map(Rule::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Target {
+    Accept,
+    Reject,
+    Workflow(usize),
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    // attribute index, comparison and value
+    condition: Option<(usize, Cmp, u64)>,
+    target: Target,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CompileError {
+    DuplicateWorkflow(String),
+    UnknownTarget { workflow: String, target: String },
+    UnknownAttribute { workflow: String, attr: String },
+    NoFallback(String),
+    MissingStart(String),
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::DuplicateWorkflow(name) => write!(f, "workflow {name} defined twice"),
+            CompileError::UnknownTarget { workflow, target } => {
+                write!(f, "workflow {workflow} sends to unknown workflow {target}")
+            }
+            CompileError::UnknownAttribute { workflow, attr } => {
+                write!(f, "workflow {workflow} tests unknown attribute {attr}")
+            }
+            CompileError::NoFallback(name) => {
+                write!(f, "workflow {name} doesn't end with an unconditional rule")
+            }
+            CompileError::MissingStart(name) => write!(f, "no start workflow {name}"),
+            CompileError::Cycle(names) => write!(f, "workflows cycle: {}", names.join(" -> ")),
+        }
+    }
+}
+
+/// Workflows compiled into a decision tree where workflows and attributes are
+/// referred to by index.
+#[derive(Debug)]
+pub struct Engine {
+    attrs: Vec<String>,
+    names: Vec<String>,
+    workflows: Vec<Vec<CompiledRule>>,
+    start: usize,
+}
+
+impl Engine {
+    pub fn compile(
+        workflows: &[Workflow],
+        attrs: &[&str],
+        start: &str,
+    ) -> Result<Engine, CompileError> {
+        let mut ids = HashMap::new();
+        for (id, workflow) in workflows.iter().enumerate() {
+            if ids.insert(workflow.name.as_str(), id).is_some() {
+                return Err(CompileError::DuplicateWorkflow(workflow.name.clone()));
+            }
+        }
+
+        let compiled = workflows
+            .iter()
+            .map(|workflow| {
+                if !matches!(
+                    workflow.rules.last(),
+                    Some(Rule {
+                        condition: None,
+                        ..
+                    })
+                ) {
+                    return Err(CompileError::NoFallback(workflow.name.clone()));
+                }
+
+                workflow
+                    .rules
+                    .iter()
+                    .map(|rule| {
+                        let target = match rule.target.as_str() {
+                            "A" => Target::Accept,
+                            "R" => Target::Reject,
+                            name => Target::Workflow(*ids.get(name).ok_or_else(|| {
+                                CompileError::UnknownTarget {
+                                    workflow: workflow.name.clone(),
+                                    target: name.to_owned(),
+                                }
+                            })?),
+                        };
+                        let condition = match &rule.condition {
+                            None => None,
+                            Some(c) => {
+                                let attr =
+                                    attrs.iter().position(|&a| a == c.attr).ok_or_else(|| {
+                                        CompileError::UnknownAttribute {
+                                            workflow: workflow.name.clone(),
+                                            attr: c.attr.clone(),
+                                        }
+                                    })?;
+                                Some((attr, c.cmp, c.value))
+                            }
+                        };
+                        Ok(CompiledRule { condition, target })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let engine = Engine {
+            attrs: attrs.iter().map(|&a| a.to_owned()).collect(),
+            names: workflows.iter().map(|w| w.name.clone()).collect(),
+            start: *ids
+                .get(start)
+                .ok_or_else(|| CompileError::MissingStart(start.to_owned()))?,
+            workflows: compiled,
+        };
+        engine.check_cycles()?;
+
+        Ok(engine)
+    }
+
+    fn check_cycles(&self) -> Result<(), CompileError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            InProgress,
+            Done,
+        }
+
+        fn visit(
+            engine: &Engine,
+            id: usize,
+            marks: &mut [Mark],
+            stack: &mut Vec<usize>,
+        ) -> Result<(), CompileError> {
+            match marks[id] {
+                Mark::Done => return Ok(()),
+                Mark::InProgress => {
+                    let start = stack.iter().position(|&s| s == id).unwrap();
+                    let mut cycle = stack[start..]
+                        .iter()
+                        .map(|&s| engine.names[s].clone())
+                        .collect::<Vec<_>>();
+                    cycle.push(engine.names[id].clone());
+                    return Err(CompileError::Cycle(cycle));
+                }
+                Mark::New => {}
+            }
+
+            marks[id] = Mark::InProgress;
+            stack.push(id);
+            for rule in &engine.workflows[id] {
+                if let Target::Workflow(next) = rule.target {
+                    visit(engine, next, marks, stack)?;
+                }
+            }
+            stack.pop();
+            marks[id] = Mark::Done;
+
+            Ok(())
+        }
+
+        let mut marks = vec![Mark::New; self.workflows.len()];
+        (0..self.workflows.len()).try_for_each(|id| visit(self, id, &mut marks, &mut Vec::new()))
+    }
+
+    pub fn attributes(&self) -> &[String] {
+        &self.attrs
+    }
+
+    /// Whether a part is accepted, its ratings given in `attributes()` order.
+    pub fn accepts(&self, part: &[u64]) -> bool {
+        let mut workflow = self.start;
+
+        loop {
+            let rule = self.workflows[workflow]
+                .iter()
+                .find(|rule| match rule.condition {
+                    None => true,
+                    Some((attr, Cmp::Lt, value)) => part[attr] < value,
+                    Some((attr, Cmp::Gt, value)) => part[attr] > value,
+                })
+                .unwrap();
+
+            match rule.target {
+                Target::Accept => return true,
+                Target::Reject => return false,
+                Target::Workflow(next) => workflow = next,
+            }
+        }
+    }
+
+    /// Disjoint hyper-rectangles of accepted parts within `bounds`.
+    pub fn accepted(&self, bounds: &[RangeInclusive<u64>]) -> Vec<Vec<RangeInclusive<u64>>> {
+        // half-open ranges internally, so that empty ones are easy to spot, in
+        // u128 so that their end can be past u64::MAX
+        let bounds = bounds
+            .iter()
+            .map(|r| (*r.start() as u128, *r.end() as u128 + 1))
+            .collect::<Vec<_>>();
+        let mut accepted = Vec::new();
+        self.split(self.start, bounds, &mut accepted);
+
+        accepted
+            .into_iter()
+            .map(|b| {
+                b.into_iter()
+                    .map(|(lo, hi)| lo as u64..=(hi - 1) as u64)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn split(
+        &self,
+        workflow: usize,
+        mut ranges: Vec<(u128, u128)>,
+        accepted: &mut Vec<Vec<(u128, u128)>>,
+    ) {
+        for rule in &self.workflows[workflow] {
+            let mut matching = ranges.clone();
+            if let Some((attr, cmp, value)) = rule.condition {
+                // x>1000 gives two ranges: (1001, 4001) follows the rule target,
+                // (1, 1001) goes on with the next rule
+                let (lo, hi) = ranges[attr];
+                let value = value as u128;
+                let (matched, rest) = match cmp {
+                    Cmp::Lt => ((lo, hi.min(value)), (lo.max(value), hi)),
+                    Cmp::Gt => ((lo.max(value + 1), hi), (lo, hi.min(value + 1))),
+                };
+                matching[attr] = matched;
+                ranges[attr] = rest;
+            }
+
+            if matching.iter().all(|(lo, hi)| lo < hi) {
+                match rule.target {
+                    Target::Accept => accepted.push(matching),
+                    Target::Reject => {}
+                    Target::Workflow(next) => self.split(next, matching, accepted),
+                }
+            }
+            if ranges.iter().any(|(lo, hi)| lo >= hi) {
+                break;
+            }
+        }
+    }
+
+    /// Number of accepted parts within `bounds`.
+    pub fn count_accepted(&self, bounds: &[RangeInclusive<u64>]) -> u64 {
+        self.accepted(bounds)
+            .iter()
+            .map(|b| b.iter().map(|r| r.end() - r.start() + 1).product::<u64>())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(workflows: &str, attrs: &[&str]) -> Result<Engine, CompileError> {
+        let workflows = workflows.lines().map(Workflow::from).collect::<Vec<_>>();
+        Engine::compile(&workflows, attrs, "in")
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert_eq!(
+            compile("in{x<5:foo,A}", &["x"]).unwrap_err(),
+            CompileError::UnknownTarget {
+                workflow: "in".to_owned(),
+                target: "foo".to_owned()
+            }
+        );
+        assert_eq!(
+            compile("in{y<5:R,A}", &["x"]).unwrap_err(),
+            CompileError::UnknownAttribute {
+                workflow: "in".to_owned(),
+                attr: "y".to_owned()
+            }
+        );
+        assert_eq!(
+            compile("in{x<5:R}", &["x"]).unwrap_err(),
+            CompileError::NoFallback("in".to_owned())
+        );
+        assert_eq!(
+            compile("start{A}", &["x"]).unwrap_err(),
+            CompileError::MissingStart("in".to_owned())
+        );
+        assert_eq!(
+            compile("in{x<5:a,A}\na{x>2:b,R}\nb{a}", &["x"]).unwrap_err(),
+            CompileError::Cycle(vec!["a".to_owned(), "b".to_owned(), "a".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_attributes() {
+        let engine = compile(
+            "in{speed>10:slow,A}\nslow{weight<3:A,R}",
+            &["speed", "weight"],
+        )
+        .unwrap();
+        assert!(engine.accepts(&[5, 100]));
+        assert!(engine.accepts(&[20, 2]));
+        assert!(!engine.accepts(&[20, 3]));
+
+        // speed 0..=10 any weight: 11 * 10, speed 11..=20 weight 0..=2: 10 * 3
+        assert_eq!(engine.count_accepted(&[0..=20, 0..=9]), 140);
+        assert_eq!(
+            engine.accepted(&[0..=20, 0..=9]),
+            vec![vec![11..=20, 0..=2], vec![0..=10, 0..=9]]
+        );
+
+        // up to the largest ratings
+        let engine = compile(&format!("in{{x>{}:R,A}}", u64::MAX - 1), &["x"]).unwrap();
+        assert_eq!(
+            engine.accepted(&[u64::MAX - 5..=u64::MAX]),
+            vec![vec![u64::MAX - 5..=u64::MAX - 1]]
+        );
+        let engine = compile(&format!("in{{x>{}:A,R}}", u64::MAX), &["x"]).unwrap();
+        assert_eq!(engine.accepted(&[0..=u64::MAX]), Vec::<Vec<_>>::new());
+    }
+}
@@ -1,202 +1,60 @@
+mod engine;
+
+use engine::{Engine, Workflow};
 use std::collections::HashMap;
+use utils::parse;
 
-#[derive(Debug)]
-struct RuleEval {
-    part: char,
-    op: char,
-    value: usize,
-}
+const ATTRIBUTES: [&str; 4] = ["x", "m", "a", "s"];
+const MIN_RATING: u64 = 1;
+const MAX_RATING: u64 = 4000;
 
-#[derive(Clone, Debug, PartialEq)]
-enum RuleAction {
-    Rule(String),
-    Accept,
-    Reject,
-}
+type Part = HashMap<String, u64>;
 
-impl From<&str> for RuleAction {
-    fn from(s: &str) -> Self {
-        match s {
-            "A" => RuleAction::Accept,
-            "R" => RuleAction::Reject,
-            _ => RuleAction::Rule(s.to_owned()),
-        }
-    }
-}
+fn parse(input: &str) -> (Vec<Workflow>, Vec<Part>) {
+    let [workflows, parts] = parse::sections_n(input).unwrap();
 
-#[derive(Debug)]
-struct Rule {
-    eval: Option<RuleEval>,
-    action: RuleAction,
-}
+    let workflows = workflows.lines().map(Workflow::from).collect();
 
-impl From<&str> for Rule {
-    fn from(s: &str) -> Self {
-        let pos_op = s.find('>').or_else(|| s.find('<'));
-        let pos_action = s.find(':');
-        match (pos_op, pos_action) {
-            (None, None) => Rule {
-                eval: None,
-                action: RuleAction::from(s),
-            },
-            (Some(pos_op), Some(pos_action)) => {
-                let part = s.chars().next().unwrap();
-                let op = s.chars().nth(pos_op).unwrap();
-                let value = s[pos_op + 1..pos_action].parse::<usize>().unwrap();
-                let action = &s[pos_action + 1..];
-
-                Rule {
-                    eval: Some(RuleEval { part, op, value }),
-                    action: RuleAction::from(action),
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Part {
-    name: char,
-    value: usize,
-}
-
-fn parse(input: &str) -> (HashMap<String, Vec<Rule>>, Vec<[Part; 4]>) {
-    let (rules, parts) = input.split_once("\n\n").unwrap();
-
-    let rules: HashMap<_, _> = rules
+    let parts = parts
         .lines()
         .map(|l| {
-            let (name, rules) = l.split_once('{').unwrap();
-
-            let rules: Vec<_> = rules[..rules.len() - 1]
+            l.trim_matches(['{', '}'])
                 .split(',')
-                .map(Rule::from)
-                .collect();
-
-            (name.to_owned(), rules)
-        })
-        .collect();
-
-    let parts: Vec<[Part; 4]> = parts
-        .lines()
-        .map(|l| {
-            let s = &l[1..l.len() - 1];
-            s.splitn(4, ',')
-                .map(|p| Part {
-                    name: p.chars().next().unwrap(),
-                    value: p[2..].parse().unwrap(),
+                .map(|rating| {
+                    let (name, value) = parse::split_once(rating, "=")?;
+                    Ok((name.to_owned(), parse::number(value)?))
                 })
-                .collect::<Vec<_>>()
-                .try_into()
+                .collect::<parse::Result<_>>()
                 .unwrap()
         })
         .collect();
 
-    (rules, parts)
-}
-
-fn eval_xmas_parts(parts: &[Part; 4], rules: &[Rule]) -> RuleAction {
-    for rule in rules {
-        if let Some(eval) = &rule.eval {
-            let part = parts.iter().find(|p| p.name == eval.part).unwrap();
-
-            match eval.op {
-                '>' => {
-                    if part.value > eval.value {
-                        return rule.action.clone();
-                    }
-                }
-                '<' => {
-                    if part.value < eval.value {
-                        return rule.action.clone();
-                    }
-                }
-                _ => unreachable!(),
-            }
-        } else {
-            return rule.action.clone();
-        }
-    }
-
-    unreachable!()
+    (workflows, parts)
 }
 
-fn part1(input: &str) -> usize {
-    let mut result = 0;
+fn part1(input: &str) -> u64 {
     let (workflows, parts) = parse(input);
+    let engine = Engine::compile(&workflows, &ATTRIBUTES, "in").unwrap();
 
-    for xmas in parts {
-        let mut rules = workflows.get("in").unwrap();
-        loop {
-            let action = eval_xmas_parts(&xmas, rules);
-
-            if let RuleAction::Rule(next) = action {
-                rules = workflows.get(&next).unwrap();
-            } else {
-                if action == RuleAction::Accept {
-                    result += xmas.iter().map(|p| p.value).sum::<usize>();
-                }
-                break;
-            }
-        }
-    }
-
-    result
-}
-
-const MIN_RATING: usize = 1;
-const MAX_RATING: usize = 4000;
-
-fn combinations(
-    workflows: &HashMap<String, Vec<Rule>>,
-    workflow: &str,
-    xmas: [(usize, usize); 4],
-) -> usize {
-    let rules = workflows.get(workflow).unwrap();
-    let mut exclude_xmas = xmas;
-
-    rules
+    parts
         .iter()
-        .map(|rule| {
-            let mut include_xmas = exclude_xmas;
-            if let Some(eval) = &rule.eval {
-                let (range_incl, range_excl) = match eval.part {
-                    'x' => (&mut include_xmas[0], &mut exclude_xmas[0]),
-                    'm' => (&mut include_xmas[1], &mut exclude_xmas[1]),
-                    'a' => (&mut include_xmas[2], &mut exclude_xmas[2]),
-                    's' => (&mut include_xmas[3], &mut exclude_xmas[3]),
-                    _ => unreachable!(),
-                };
-
-                // x>1000 gives two ranges : (1001, 4000) and (0, 1000)
-                // first one gets passed to the call chain (recursion)
-                // second one is for the next iteration
-                if eval.op == '<' {
-                    *range_excl = (eval.value, range_excl.1.max(eval.value));
-                    *range_incl = (range_incl.0, range_incl.1.min(eval.value - 1));
-                } else {
-                    *range_excl = (range_incl.0, eval.value.min(range_excl.1));
-                    *range_incl = (range_incl.0.max(eval.value + 1), range_incl.1);
-                }
-            }
-
-            match &rule.action {
-                RuleAction::Accept => include_xmas
-                    .iter()
-                    .map(|(min, max)| max - min + 1)
-                    .product(),
-                RuleAction::Reject => 0,
-                RuleAction::Rule(next) => combinations(workflows, next, include_xmas),
-            }
+        .filter(|part| {
+            let ratings = engine
+                .attributes()
+                .iter()
+                .map(|attr| part[attr])
+                .collect::<Vec<_>>();
+            engine.accepts(&ratings)
         })
+        .map(|part| part.values().sum::<u64>())
         .sum()
 }
 
-fn part2(input: &str) -> usize {
+fn part2(input: &str) -> u64 {
     let (workflows, _) = parse(input);
+    let engine = Engine::compile(&workflows, &ATTRIBUTES, "in").unwrap();
 
-    combinations(&workflows, "in", [(MIN_RATING, MAX_RATING); 4])
+    engine.count_accepted(&[const { MIN_RATING..=MAX_RATING }; 4])
 }
 
 fn main() {
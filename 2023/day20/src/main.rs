@@ -1,184 +1,127 @@
-use std::cell::{Cell, RefCell};
-use std::collections::{HashMap, VecDeque};
-
-#[derive(Debug)]
-enum Module {
-    Broadcaster {
-        targets: Vec<String>,
-    },
-    FlipFlop {
-        name: String,
-        targets: Vec<String>,
-        state: Cell<FlipFlopState>,
-    },
-    Conjonction {
-        name: String,
-        targets: Vec<String>,
-        state: RefCell<ConjonctionInputsState>,
-    },
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum FlipFlopState {
-    On,
-    Off,
-}
+mod network;
 
-type ConjonctionInputsState = HashMap<String, Pulse>;
+use network::{Kind, Network, Pulse};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Pulse {
-    Low,
-    High,
+#[derive(Debug, PartialEq)]
+enum AnalysisError {
+    NoFeeder,
+    MultipleFeeders(Vec<String>),
+    FeederNotConjonction(String),
+    NoCycle { input: String, presses: usize },
+    IrregularCycle { input: String, hits: (usize, usize) },
 }
 
-fn parse(input: &str) -> HashMap<String, Module> {
-    let modules: HashMap<_, _> = input
-        .lines()
-        .map(|l| {
-            let (from, to) = l.split_once(" -> ").unwrap();
-            let targets: Vec<_> = to.split(", ").map(|t| t.to_owned()).collect();
-            match (&from[..1], &from[1..]) {
-                ("b", "roadcaster") => ("broadcaster".to_owned(), Module::Broadcaster { targets }),
-                ("%", name) => (
-                    name.to_owned(),
-                    Module::FlipFlop {
-                        name: name.to_owned(),
-                        targets,
-                        state: Cell::new(FlipFlopState::Off),
-                    },
-                ),
-                ("&", name) => (
-                    name.to_owned(),
-                    Module::Conjonction {
-                        name: name.to_owned(),
-                        targets,
-                        state: RefCell::new(HashMap::new()),
-                    },
-                ),
-                _ => unreachable!(),
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::NoFeeder => write!(f, "no module sends pulses to rx"),
+            AnalysisError::MultipleFeeders(names) => {
+                write!(f, "rx is fed by several modules: {}", names.join(", "))
             }
-        })
-        .collect();
-
-    for (name, module) in &modules {
-        let targets = match module {
-            Module::Broadcaster { targets } => targets,
-            Module::FlipFlop { targets, .. } => targets,
-            Module::Conjonction { targets, .. } => targets,
-        };
-
-        for target in targets {
-            if let Some(Module::Conjonction { state, .. }) = modules.get(target) {
-                let mut state = state.borrow_mut();
-                state.insert(name.to_owned(), Pulse::Low);
+            AnalysisError::FeederNotConjonction(name) => {
+                write!(f, "rx is fed by {name} which isn't a conjonction")
             }
+            AnalysisError::NoCycle { input, presses } => {
+                write!(f, "{input} didn't cycle within {presses} presses")
+            }
+            AnalysisError::IrregularCycle { input, hits } => write!(
+                f,
+                "{input} sends high pulses at presses {} and {}, not a cycle starting at 0",
+                hits.0, hits.1
+            ),
         }
     }
-
-    modules
 }
 
-fn press_button(modules: &HashMap<String, Module>) -> (usize, usize, Option<String>) {
-    let mut low_pulses = 0;
-    let mut high_pulses = 0;
-    let mut ft_high_pulse_from = None;
-    let mut queue = VecDeque::from([("", "broadcaster", Pulse::Low)]);
+fn part1(input: &str) -> usize {
+    let mut network = Network::parse(input);
 
-    while let Some((from, to, pulse)) = queue.pop_front() {
-        match pulse {
-            Pulse::Low => low_pulses += 1,
-            Pulse::High => high_pulses += 1,
-        }
+    let (low_pulses, high_pulses) = (0..1000)
+        .map(|_| network.press_count())
+        .fold((0, 0), |(low, high), (l, h)| (low + l, high + h));
 
-        if to == "output" || to == "rx" {
-            continue;
-        }
+    low_pulses * high_pulses
+}
 
-        let module = modules.get(to).unwrap();
-        match module {
-            Module::Broadcaster { targets } => {
-                targets
-                    .iter()
-                    .for_each(|target| queue.push_back(("", target, pulse)));
-            }
-            Module::FlipFlop {
-                name,
-                targets,
-                state,
-            } => match pulse {
-                Pulse::Low => {
-                    let new_pulse = if state.get() == FlipFlopState::On {
-                        state.set(FlipFlopState::Off);
-                        Pulse::Low
-                    } else {
-                        state.set(FlipFlopState::On);
-                        Pulse::High
-                    };
-                    targets
-                        .iter()
-                        .for_each(|target| queue.push_back((name, target, new_pulse)));
-                }
-                Pulse::High => {}
-            },
-            Module::Conjonction {
-                name,
-                targets,
-                state,
-            } => {
-                let mut state = state.borrow_mut();
-                state.entry(from.to_string()).and_modify(|p| *p = pulse);
-                let new_pulse = if state.values().all(|&p| p == Pulse::High) {
-                    Pulse::Low
-                } else {
-                    Pulse::High
-                };
-                targets
+// rx receives a low pulse when all the inputs of the conjonction feeding it
+// sent a high pulse during the same press. Each input does so periodically,
+// so the answer is the lcm of their periods.
+fn part2(input: &str) -> Result<usize, AnalysisError> {
+    const MAX_PRESSES: usize = 1 << 16;
+
+    let mut network = Network::parse(input);
+    let feeders = match network.id("rx") {
+        Some(rx) => network.module(rx).inputs.clone(),
+        None => Vec::new(),
+    };
+    let feeder = match feeders[..] {
+        [] => return Err(AnalysisError::NoFeeder),
+        [feeder] => feeder,
+        _ => {
+            return Err(AnalysisError::MultipleFeeders(
+                feeders
                     .iter()
-                    .for_each(|target| queue.push_back((name, target, new_pulse)));
-                // part2
-                if name == "ft" && pulse == Pulse::High {
-                    ft_high_pulse_from = Some(from.to_owned());
-                }
-            }
+                    .map(|&id| network.module(id).name.clone())
+                    .collect(),
+            ))
         }
+    };
+    if network.module(feeder).kind != Kind::Conjonction {
+        return Err(AnalysisError::FeederNotConjonction(
+            network.module(feeder).name.clone(),
+        ));
     }
 
-    (low_pulses, high_pulses, ft_high_pulse_from)
-}
-
-fn part1(input: &str) -> usize {
-    let modules = parse(input);
+    let inputs = network.module(feeder).inputs.clone();
+    let mut hits: Vec<Vec<usize>> = vec![Vec::new(); inputs.len()];
 
-    let (low_pulses, high_pulses) = (0..1000)
-        .map(|_| press_button(&modules))
-        .fold((0, 0), |(low, high), (l, h, _)| (low + l, high + h));
-
-    low_pulses * high_pulses
-}
+    while network.presses() < MAX_PRESSES && hits.iter().any(|h| h.len() < 2) {
+        let count = network.presses() + 1;
+        network.press(|event| {
+            if event.to == feeder && event.pulse == Pulse::High {
+                let input = inputs.iter().position(|&i| Some(i) == event.from).unwrap();
+                if hits[input].last() != Some(&count) {
+                    hits[input].push(count);
+                }
+            }
+        });
+    }
 
-fn part2(input: &str) -> usize {
-    let modules = parse(input);
-    let mut cycles = HashMap::from([
-        ("vz".to_owned(), 0),
-        ("lt".to_owned(), 0),
-        ("bq".to_owned(), 0),
-        ("qh".to_owned(), 0),
-    ]);
-
-    for count in 1..5000usize {
-        let (_, _, ft_high_pulse_from) = press_button(&modules);
-        if let Some(src) = ft_high_pulse_from {
-            cycles.insert(src, count);
+    let mut cycles = Vec::new();
+    for (&id, h) in inputs.iter().zip(hits) {
+        let input = network.module(id).name.clone();
+        match h[..] {
+            [first, second, ..] if second == 2 * first => cycles.push(first),
+            [first, second, ..] => {
+                return Err(AnalysisError::IrregularCycle {
+                    input,
+                    hits: (first, second),
+                })
+            }
+            _ => {
+                return Err(AnalysisError::NoCycle {
+                    input,
+                    presses: MAX_PRESSES,
+                })
+            }
         }
     }
 
-    cycles.values().copied().reduce(num::integer::lcm).unwrap()
+    Ok(cycles.into_iter().reduce(num::integer::lcm).unwrap_or(1))
 }
 
 fn main() {
+    // cargo run -- --dot | dot -Tsvg > network.svg
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", Network::parse(include_str!("../input.txt")).to_dot());
+        return;
+    }
+
     println!("part1={}", part1(include_str!("../input.txt")));
-    println!("part2={}", part2(include_str!("../input.txt")));
+    match part2(include_str!("../input.txt")) {
+        Ok(presses) => println!("part2={presses}"),
+        Err(e) => println!("part2: {e}"),
+    }
 }
 
 #[cfg(test)]
@@ -194,10 +137,26 @@ mod tests {
 &inv -> b
 %b -> con
 &con -> output";
+    const TEST_INPUT3: &str = "broadcaster -> a
+%a -> ia, a2
+%a2 -> ib
+&ia -> hub
+&ib -> hub
+&hub -> rx";
 
     #[test]
     fn test_part1() {
         assert_eq!(part1(TEST_INPUT), 32000000);
         assert_eq!(part1(TEST_INPUT2), 11687500);
     }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(TEST_INPUT3), Ok(4));
+        assert_eq!(part2(TEST_INPUT), Err(AnalysisError::NoFeeder));
+        assert_eq!(
+            part2(&TEST_INPUT3.replace("&hub", "%hub")),
+            Err(AnalysisError::FeederNotConjonction("hub".to_owned()))
+        );
+    }
 }
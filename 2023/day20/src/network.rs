@@ -0,0 +1,287 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pulse {
+    Low,
+    High,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Broadcaster,
+    FlipFlop,
+    Conjonction,
+    /// Only referenced as a target, e.g. output or rx
+    Untyped,
+}
+
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub kind: Kind,
+    pub targets: Vec<usize>,
+    pub inputs: Vec<usize>,
+}
+
+/// Everything that changes when pressing the button.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct State {
+    pub presses: usize,
+    flip_flops: Vec<bool>,
+    // last pulse received by each conjonction, indexed like `Module::inputs`
+    memory: Vec<Vec<Pulse>>,
+}
+
+/// A pulse travelling between two modules; `from` is `None` for the button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub from: Option<usize>,
+    pub to: usize,
+    pub pulse: Pulse,
+}
+
+#[derive(Debug)]
+pub struct Network {
+    modules: Vec<Module>,
+    ids: HashMap<String, usize>,
+    state: State,
+}
+
+impl Network {
+    pub fn parse(input: &str) -> Self {
+        let mut network = Network {
+            modules: Vec::new(),
+            ids: HashMap::new(),
+            state: State::default(),
+        };
+
+        let lines = input
+            .lines()
+            .map(|l| {
+                let (from, to) = l.split_once(" -> ").unwrap();
+                let (kind, name) = match (&from[..1], &from[1..]) {
+                    ("b", "roadcaster") => (Kind::Broadcaster, from),
+                    ("%", name) => (Kind::FlipFlop, name),
+                    ("&", name) => (Kind::Conjonction, name),
+                    _ => unreachable!(),
+                };
+                let id = network.id_or_insert(name);
+                network.modules[id].kind = kind;
+                (id, to)
+            })
+            .collect::<Vec<_>>();
+
+        for (id, to) in lines {
+            for target in to.split(", ") {
+                let target = network.id_or_insert(target);
+                network.modules[id].targets.push(target);
+                network.modules[target].inputs.push(id);
+            }
+        }
+
+        network.reset();
+        network
+    }
+
+    fn id_or_insert(&mut self, name: &str) -> usize {
+        *self.ids.entry(name.to_owned()).or_insert_with(|| {
+            self.modules.push(Module {
+                name: name.to_owned(),
+                kind: Kind::Untyped,
+                targets: Vec::new(),
+                inputs: Vec::new(),
+            });
+            self.modules.len() - 1
+        })
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn module(&self, id: usize) -> &Module {
+        &self.modules[id]
+    }
+
+    pub fn presses(&self) -> usize {
+        self.state.presses
+    }
+
+    #[cfg(test)]
+    pub fn snapshot(&self) -> State {
+        self.state.clone()
+    }
+
+    #[cfg(test)]
+    pub fn restore(&mut self, state: &State) {
+        self.state.clone_from(state);
+    }
+
+    /// All flip-flops off, all conjonctions remembering low pulses.
+    pub fn reset(&mut self) {
+        self.state = State {
+            presses: 0,
+            flip_flops: vec![false; self.modules.len()],
+            memory: self
+                .modules
+                .iter()
+                .map(|m| vec![Pulse::Low; m.inputs.len()])
+                .collect(),
+        };
+    }
+
+    /// Press the button and deliver every pulse in order, calling `on_event`
+    /// as each one is received.
+    pub fn press(&mut self, mut on_event: impl FnMut(&Event)) {
+        self.state.presses += 1;
+
+        let Some(broadcaster) = self.id("broadcaster") else {
+            return;
+        };
+        let mut queue = VecDeque::from([Event {
+            from: None,
+            to: broadcaster,
+            pulse: Pulse::Low,
+        }]);
+
+        while let Some(event) = queue.pop_front() {
+            on_event(&event);
+
+            let to = event.to;
+            let module = &self.modules[to];
+            let pulse = match (module.kind, event.pulse) {
+                (Kind::Broadcaster, pulse) => pulse,
+                (Kind::FlipFlop, Pulse::Low) => {
+                    let on = &mut self.state.flip_flops[to];
+                    *on = !*on;
+                    if *on {
+                        Pulse::High
+                    } else {
+                        Pulse::Low
+                    }
+                }
+                (Kind::Conjonction, pulse) => {
+                    let from = event.from.unwrap();
+                    let memory = &mut self.state.memory[to];
+                    let input = module.inputs.iter().position(|&i| i == from).unwrap();
+                    memory[input] = pulse;
+                    if memory.iter().all(|&p| p == Pulse::High) {
+                        Pulse::Low
+                    } else {
+                        Pulse::High
+                    }
+                }
+                (Kind::FlipFlop, Pulse::High) | (Kind::Untyped, _) => continue,
+            };
+
+            queue.extend(module.targets.iter().map(|&target| Event {
+                from: Some(to),
+                to: target,
+                pulse,
+            }));
+        }
+    }
+
+    /// Press the button, returning the number of low and high pulses sent.
+    pub fn press_count(&mut self) -> (usize, usize) {
+        let (mut low, mut high) = (0, 0);
+        self.press(|event| match event.pulse {
+            Pulse::Low => low += 1,
+            Pulse::High => high += 1,
+        });
+
+        (low, high)
+    }
+
+    /// Graphviz description of the network. Flip-flops are filled when on.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph network {\n");
+
+        for (id, module) in self.modules.iter().enumerate() {
+            let attrs = match module.kind {
+                Kind::Broadcaster => "shape=box".to_owned(),
+                Kind::FlipFlop if self.state.flip_flops[id] => {
+                    "shape=circle, style=filled, fillcolor=gold".to_owned()
+                }
+                Kind::FlipFlop => "shape=circle".to_owned(),
+                Kind::Conjonction => "shape=diamond, color=red".to_owned(),
+                Kind::Untyped => "shape=doublecircle".to_owned(),
+            };
+            writeln!(dot, "  {} [{attrs}];", module.name).unwrap();
+        }
+        for module in &self.modules {
+            for &target in &module.targets {
+                writeln!(dot, "  {} -> {};", module.name, self.modules[target].name).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_INPUT: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    #[test]
+    fn test_events() {
+        let mut network = Network::parse(TEST_INPUT);
+        let mut events = Vec::new();
+        network.press(|event| events.push(*event));
+
+        let name = |id: Option<usize>| id.map_or("button", |id| &network.module(id).name);
+        let trace = events
+            .iter()
+            .map(|e| format!("{} -{:?}-> {}", name(e.from), e.pulse, name(Some(e.to))))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trace,
+            vec![
+                "button -Low-> broadcaster",
+                "broadcaster -Low-> a",
+                "a -High-> inv",
+                "a -High-> con",
+                "inv -Low-> b",
+                "con -High-> output",
+                "b -High-> con",
+                "con -Low-> output",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut network = Network::parse(TEST_INPUT);
+        let initial = network.snapshot();
+        network.press_count();
+        let state = network.snapshot();
+        let counts = network.press_count();
+        assert_ne!(network.snapshot(), state);
+
+        network.restore(&state);
+        assert_eq!(network.press_count(), counts);
+        assert_eq!(network.presses(), 2);
+
+        network.reset();
+        assert_eq!(network.snapshot(), initial);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut network = Network::parse(TEST_INPUT);
+        network.press_count();
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("  a [shape=circle, style=filled, fillcolor=gold];\n"));
+        assert!(dot.contains("  con [shape=diamond, color=red];\n"));
+        assert!(dot.contains("  output [shape=doublecircle];\n"));
+        assert!(dot.contains("  a -> inv;\n  a -> con;\n"));
+    }
+}
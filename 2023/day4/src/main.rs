@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use regex::Regex;
+use utils::parse;
 
 #[derive(Debug)]
 struct Card {
@@ -9,30 +9,15 @@ struct Card {
 }
 
 fn parse(input: &str) -> Vec<Card> {
-    let re = Regex::new(r"((?:\d+\s*)+)\|\s*((?:\d+\s*)+)$").unwrap();
-
-    input
-        .lines()
-        .map(|l| {
-            let caps = re.captures(l).unwrap();
-            Card {
-                winning_nums: caps
-                    .get(1)
-                    .unwrap()
-                    .as_str()
-                    .split_whitespace()
-                    .map(|n| n.parse().unwrap())
-                    .collect(),
-                nums: caps
-                    .get(2)
-                    .unwrap()
-                    .as_str()
-                    .split_whitespace()
-                    .map(|n| n.parse().unwrap())
-                    .collect(),
-            }
+    parse::lines(input, |l| {
+        let (_, numbers) = parse::key_value(l)?;
+        let (winning_nums, nums) = parse::split_once(numbers, "|")?;
+        Ok(Card {
+            winning_nums: parse::words(winning_nums)?,
+            nums: parse::words(nums)?,
         })
-        .collect()
+    })
+    .unwrap()
 }
 
 fn part1(input: &str) -> usize {
@@ -0,0 +1,252 @@
+use std::ops::Range;
+use utils::parse;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mapping {
+    pub dst: u64,
+    pub src: u64,
+    pub len: u64,
+}
+
+impl Mapping {
+    fn sources(&self) -> Range<u64> {
+        self.src..self.src + self.len
+    }
+}
+
+/// Conversion from one category to the next one. Values outside of every
+/// mapping are kept as is.
+#[derive(Debug)]
+pub struct Map {
+    pub from: String,
+    pub to: String,
+    // sorted by source
+    mappings: Vec<Mapping>,
+}
+
+impl Map {
+    /// A `seed-to-soil map:` header followed by `dst src len` lines.
+    pub fn parse(s: &str) -> parse::Result<Self> {
+        let (header, body) = s.split_once('\n').unwrap_or((s, ""));
+        let name = header.trim().trim_end_matches(" map:");
+        let (from, to) = parse::split_once(name, "-to-")?;
+
+        let mut mappings = parse::lines(body, |l| {
+            let [dst, src, len] = parse::integers_n(l)?;
+            Ok(Mapping { dst, src, len })
+        })?;
+        mappings.sort_by_key(|m| m.src);
+
+        Ok(Map {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            mappings,
+        })
+    }
+
+    pub fn map(&self, value: u64) -> u64 {
+        self.mappings
+            .iter()
+            .find(|m| m.sources().contains(&value))
+            .map_or(value, |m| m.dst + (value - m.src))
+    }
+
+    /// Every value mapped to `value`: the ones coming through a mapping, and
+    /// `value` itself if no mapping covers it.
+    pub fn unmap(&self, value: u64) -> Vec<u64> {
+        let mut sources = self
+            .mappings
+            .iter()
+            .filter(|m| (m.dst..m.dst + m.len).contains(&value))
+            .map(|m| m.src + (value - m.dst))
+            .collect::<Vec<_>>();
+        if !self.mappings.iter().any(|m| m.sources().contains(&value)) {
+            sources.push(value);
+        }
+        sources.sort_unstable();
+
+        sources
+    }
+
+    /// Images of `ranges`, cut along the mappings.
+    pub fn map_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+
+        for range in ranges {
+            let mut cursor = range.start;
+            for m in &self.mappings {
+                let sources = m.sources();
+                if sources.end <= cursor {
+                    continue;
+                }
+                if sources.start >= range.end {
+                    break;
+                }
+                if cursor < sources.start {
+                    mapped.push(cursor..sources.start);
+                    cursor = sources.start;
+                }
+                let end = range.end.min(sources.end);
+                mapped.push(m.dst + (cursor - m.src)..m.dst + (end - m.src));
+                cursor = end;
+            }
+            if cursor < range.end {
+                mapped.push(cursor..range.end);
+            }
+        }
+
+        merge(mapped)
+    }
+}
+
+/// Sorted disjoint ranges covering the same values.
+fn merge(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
+#[derive(Debug)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    maps: Vec<Map>,
+}
+
+impl Almanac {
+    pub fn parse(input: &str) -> parse::Result<Self> {
+        let mut sections = parse::sections(input);
+        let seeds = parse::words(parse::prefixed(
+            sections.next().unwrap_or_default(),
+            "seeds:",
+        )?)?;
+        let maps = sections.map(Map::parse).collect::<parse::Result<_>>()?;
+
+        Ok(Almanac { seeds, maps })
+    }
+
+    /// Seeds read as `start len` pairs.
+    pub fn seed_ranges(&self) -> Vec<Range<u64>> {
+        self.seeds
+            .chunks_exact(2)
+            .map(|pair| pair[0]..pair[0] + pair[1])
+            .collect()
+    }
+
+    /// Maps leading from category `from` to category `to`, in order.
+    pub fn chain(&self, from: &str, to: &str) -> Option<Vec<&Map>> {
+        let mut chain = Vec::new();
+        let mut category = from;
+
+        while category != to {
+            // a chain can't be longer than the number of maps, unless it loops
+            if chain.len() == self.maps.len() {
+                return None;
+            }
+            let map = self.maps.iter().find(|m| m.from == category)?;
+            chain.push(map);
+            category = &map.to;
+        }
+
+        Some(chain)
+    }
+
+    pub fn convert(&self, from: &str, to: &str, value: u64) -> Option<u64> {
+        Some(
+            self.chain(from, to)?
+                .iter()
+                .fold(value, |v, map| map.map(v)),
+        )
+    }
+
+    pub fn convert_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &[Range<u64>],
+    ) -> Option<Vec<Range<u64>>> {
+        let ranges = merge(ranges.to_vec());
+        Some(
+            self.chain(from, to)?
+                .iter()
+                .fold(ranges, |ranges, map| map.map_ranges(&ranges)),
+        )
+    }
+
+    /// The lowest range `ranges` are converted to.
+    pub fn lowest(&self, from: &str, to: &str, ranges: &[Range<u64>]) -> Option<Range<u64>> {
+        self.convert_ranges(from, to, ranges)?.into_iter().next()
+    }
+
+    /// Every value of category `from` converted to `value` in category `to`,
+    /// mapping a `to` value back through the same chain as `convert`.
+    pub fn reverse(&self, from: &str, to: &str, value: u64) -> Option<Vec<u64>> {
+        let values = self
+            .chain(from, to)?
+            .iter()
+            .rev()
+            .fold(vec![value], |values, map| {
+                let mut sources = values
+                    .iter()
+                    .flat_map(|&v| map.unmap(v))
+                    .collect::<Vec<_>>();
+                sources.sort_unstable();
+                sources.dedup();
+                sources
+            });
+
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_ranges() {
+        let map = Map::parse("a-to-b map:\n50 98 2\n52 50 48").unwrap();
+        assert_eq!(map.map(79), 81);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(map.map(10), 10);
+        assert_eq!(
+            map.map_ranges(&[40..60, 120..130]),
+            vec![40..50, 52..62, 120..130]
+        );
+        assert_eq!(
+            map.map_ranges(&[97..102, 0..10]),
+            vec![0..10, 50..52, 99..102]
+        );
+
+        // 50 is mapped away itself, so only 98 gives 50
+        assert_eq!(map.unmap(50), vec![98]);
+        assert_eq!(map.unmap(52), vec![50]);
+        assert_eq!(map.unmap(10), vec![10]);
+    }
+
+    #[test]
+    fn test_chain() {
+        let almanac = Almanac::parse(
+            "seeds: 1 5\n\nb-to-c map:\n10 0 5\n\na-to-b map:\n0 3 3\n3 0 3\n\nc-to-a map:\n",
+        )
+        .unwrap();
+        assert_eq!(almanac.chain("a", "c").unwrap().len(), 2);
+        assert!(almanac.chain("a", "d").is_none());
+        assert_eq!(almanac.convert("a", "c", 4), Some(11));
+        assert_eq!(almanac.convert("b", "b", 4), Some(4));
+        assert_eq!(
+            almanac.convert_ranges("a", "c", &almanac.seed_ranges()),
+            Some(vec![5..6, 10..13, 14..15])
+        );
+        // 11 comes from 1 through b-to-c, but also stays 11 as no mapping covers it
+        assert_eq!(almanac.reverse("a", "c", 11), Some(vec![4, 11]));
+    }
+}
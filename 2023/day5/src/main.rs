@@ -1,127 +1,46 @@
-use rayon::prelude::*;
+mod almanac;
 
-#[derive(Debug, Default)]
-struct RangeMap {
-    dst: usize,
-    src: usize,
-    len: usize,
-}
-
-#[derive(Debug, Default)]
-struct Game {
-    seeds: Vec<usize>,
-    seed_to_soil: Vec<RangeMap>,
-    soil_to_fertilizer: Vec<RangeMap>,
-    fertilizer_to_water: Vec<RangeMap>,
-    water_to_light: Vec<RangeMap>,
-    light_to_temperature: Vec<RangeMap>,
-    temperature_to_humidity: Vec<RangeMap>,
-    humidity_to_location: Vec<RangeMap>,
-}
+use almanac::Almanac;
 
-impl Game {
-    fn seed_to_location(&self, seed: usize) -> usize {
-        let pipeline = [
-            &self.seed_to_soil,
-            &self.soil_to_fertilizer,
-            &self.fertilizer_to_water,
-            &self.water_to_light,
-            &self.light_to_temperature,
-            &self.temperature_to_humidity,
-            &self.humidity_to_location,
-        ];
-
-        pipeline.iter().fold(seed, |n, ranges| {
-            ranges
-                .iter()
-                .find_map(|range| {
-                    if n >= range.src && n < range.src + range.len {
-                        Some(range.dst + (n - range.src))
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(n)
-        })
-    }
-}
-
-fn parse(input: &str) -> Game {
-    let mut game = Game::default();
-
-    let entries: Vec<_> = input.split("\n\n").collect();
-
-    game.seeds = entries[0]
-        .split_once(' ')
-        .map(|(_, seeds)| {
-            seeds
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect()
-        })
-        .unwrap();
-
-    let ranges = |entry: &str| -> Vec<RangeMap> {
-        entry
-            .lines()
-            .skip(1)
-            .map(|l| {
-                let range: Vec<_> = l
-                    .split_whitespace()
-                    .map(|n| n.parse::<usize>().unwrap())
-                    .collect();
-                RangeMap {
-                    dst: range[0],
-                    src: range[1],
-                    len: range[2],
-                }
-            })
-            .collect()
-    };
-
-    game.seed_to_soil = ranges(entries[1]);
-    game.soil_to_fertilizer = ranges(entries[2]);
-    game.fertilizer_to_water = ranges(entries[3]);
-    game.water_to_light = ranges(entries[4]);
-    game.light_to_temperature = ranges(entries[5]);
-    game.temperature_to_humidity = ranges(entries[6]);
-    game.humidity_to_location = ranges(entries[7]);
-
-    game
-}
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn part1(input: &str) -> usize {
-    let game = parse(input);
+fn part1(input: &str) -> Result<u64> {
+    let almanac = Almanac::parse(input)?;
 
-    game.seeds
+    almanac
+        .seeds
         .iter()
-        .map(|&seed| game.seed_to_location(seed))
+        .filter_map(|&seed| almanac.convert("seed", "location", seed))
         .min()
-        .unwrap()
+        .ok_or_else(|| "no seed location".into())
 }
 
-fn part2(input: &str) -> usize {
-    let game = parse(input);
-
-    game.seeds
-        .chunks_exact(2)
-        .map(|chunk| match chunk {
-            &[seed_start, len] => seed_start..seed_start + len,
-            _ => panic!("wtf"),
-        })
-        .map(|seed| {
-            seed.into_par_iter()
-                .map(|seed| game.seed_to_location(seed))
-                .min()
-                .unwrap()
-        })
-        .min()
-        .unwrap()
+fn part2(input: &str) -> Result<u64> {
+    let almanac = Almanac::parse(input)?;
+
+    almanac
+        .lowest("seed", "location", &almanac.seed_ranges())
+        .map(|range| range.start)
+        .ok_or_else(|| "no seed location".into())
 }
 
-fn main() {
-    println!("part1={}", part1(include_str!("../input.txt")));
-    println!("part2={}", part2(include_str!("../input.txt")));
+fn main() -> Result<()> {
+    // cargo run -- --reverse 46
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, location] = &args[..] {
+        if flag == "--reverse" {
+            let almanac = Almanac::parse(include_str!("../input.txt"))?;
+            let seeds = almanac
+                .reverse("seed", "location", location.parse()?)
+                .ok_or("no seed-to-location chain")?;
+            println!("{seeds:?}");
+            return Ok(());
+        }
+    }
+
+    println!("part1={}", part1(include_str!("../input.txt"))?);
+    println!("part2={}", part2(include_str!("../input.txt"))?);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -163,11 +82,21 @@ humidity-to-location map:
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(TEST_INPUT), 35);
+        assert_eq!(part1(TEST_INPUT).unwrap(), 35);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(TEST_INPUT), 46);
+        assert_eq!(part2(TEST_INPUT).unwrap(), 46);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let almanac = Almanac::parse(TEST_INPUT).unwrap();
+        let seeds = almanac.reverse("seed", "location", 46).unwrap();
+        assert!(seeds.contains(&82));
+        for seed in seeds {
+            assert_eq!(almanac.convert("seed", "location", seed), Some(46));
+        }
     }
 }
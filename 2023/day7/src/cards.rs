@@ -0,0 +1,320 @@
+use std::{cmp::Ordering, fmt, marker::PhantomData, str::FromStr};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    /// Type of a hand where `wildcard` cards, if any, join the largest group
+    /// of other cards.
+    pub fn of(cards: &[char; 5], wildcard: Option<char>) -> Self {
+        let mut counts = Vec::<(char, usize)>::new();
+        let mut wildcards = 0;
+        for &card in cards {
+            if Some(card) == wildcard {
+                wildcards += 1;
+            } else if let Some((_, count)) = counts.iter_mut().find(|(c, _)| *c == card) {
+                *count += 1;
+            } else {
+                counts.push((card, 1));
+            }
+        }
+
+        let mut counts = counts.into_iter().map(|(_, n)| n).collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match counts.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => counts.push(wildcards),
+        }
+
+        match counts[..] {
+            [5] => HandType::FiveOfAKind,
+            [4, 1] => HandType::FourOfAKind,
+            [3, 2] => HandType::FullHouse,
+            [3, 1, 1] => HandType::ThreeOfAKind,
+            [2, 2, 1] => HandType::TwoPair,
+            [2, 1, 1, 1] => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A variant of the game: how strong each card is and how hands are typed.
+pub trait Rules {
+    /// Strength of a card, `None` if the card doesn't exist.
+    fn card_value(card: char) -> Option<u8>;
+
+    fn hand_type(cards: &[char; 5]) -> HandType {
+        HandType::of(cards, None)
+    }
+}
+
+fn standard_value(card: char) -> Option<u8> {
+    match card {
+        'A' => Some(14),
+        'K' => Some(13),
+        'Q' => Some(12),
+        'J' => Some(11),
+        'T' => Some(10),
+        '2'..='9' => Some(card as u8 - b'0'),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub struct Standard;
+
+impl Rules for Standard {
+    fn card_value(card: char) -> Option<u8> {
+        standard_value(card)
+    }
+}
+
+/// J cards act like whatever card makes the strongest hand, but are the
+/// weakest cards on their own.
+#[derive(Debug)]
+pub struct JokersWild;
+
+impl Rules for JokersWild {
+    fn card_value(card: char) -> Option<u8> {
+        match card {
+            'J' => Some(1),
+            card => standard_value(card),
+        }
+    }
+
+    fn hand_type(cards: &[char; 5]) -> HandType {
+        HandType::of(cards, Some('J'))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseHandError {
+    Length(usize),
+    InvalidCard(char),
+}
+
+impl fmt::Display for ParseHandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHandError::Length(len) => write!(f, "{len} cards instead of 5"),
+            ParseHandError::InvalidCard(c) => write!(f, "invalid card {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseHandError {}
+
+/// Why a hand wins or loses against another one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reason {
+    Type(HandType, HandType),
+    /// Same type, the first different card at `position` decides.
+    Card {
+        position: usize,
+        cards: (char, char),
+    },
+    Identical,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Type(left, right) => write!(f, "{left} against {right}"),
+            Reason::Card {
+                position,
+                cards: (left, right),
+            } => write!(
+                f,
+                "same type, card {} is {left} against {right}",
+                position + 1
+            ),
+            Reason::Identical => write!(f, "identical hands"),
+        }
+    }
+}
+
+pub struct Hand<R> {
+    cards: [char; 5],
+    values: [u8; 5],
+    hand_type: HandType,
+    rules: PhantomData<R>,
+}
+
+impl<R: Rules> FromStr for Hand<R> {
+    type Err = ParseHandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = s.chars().collect::<Vec<_>>();
+        let cards: [char; 5] = cards
+            .try_into()
+            .map_err(|cards: Vec<_>| ParseHandError::Length(cards.len()))?;
+
+        let mut values = [0; 5];
+        for (value, &card) in values.iter_mut().zip(&cards) {
+            *value = R::card_value(card).ok_or(ParseHandError::InvalidCard(card))?;
+        }
+
+        Ok(Hand {
+            cards,
+            values,
+            hand_type: R::hand_type(&cards),
+            rules: PhantomData,
+        })
+    }
+}
+
+impl<R> Hand<R> {
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
+
+    /// Why `self` beats, loses against or ties with `other`.
+    pub fn explain(&self, other: &Self) -> Reason {
+        if self.hand_type != other.hand_type {
+            return Reason::Type(self.hand_type, other.hand_type);
+        }
+
+        match (0..5).find(|&i| self.values[i] != other.values[i]) {
+            Some(position) => Reason::Card {
+                position,
+                cards: (self.cards[position], other.cards[position]),
+            },
+            None => Reason::Identical,
+        }
+    }
+}
+
+impl<R> fmt::Debug for Hand<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self, self.hand_type())
+    }
+}
+
+impl<R> fmt::Display for Hand<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cards.iter().try_for_each(|c| write!(f, "{c}"))
+    }
+}
+
+impl<R> PartialEq for Hand<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<R> Eq for Hand<R> {}
+
+impl<R> PartialOrd for Hand<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R> Ord for Hand<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.hand_type, self.values).cmp(&(other.hand_type, other.values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2 cards are wild and the weakest, aces are the strongest.
+    struct DeucesWild;
+
+    impl Rules for DeucesWild {
+        fn card_value(card: char) -> Option<u8> {
+            match card {
+                '2' => Some(1),
+                card => standard_value(card),
+            }
+        }
+
+        fn hand_type(cards: &[char; 5]) -> HandType {
+            HandType::of(cards, Some('2'))
+        }
+    }
+
+    fn hand<R: Rules>(s: &str) -> Hand<R> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_hand_types() {
+        assert_eq!(hand::<Standard>("QJJQ2").hand_type(), HandType::TwoPair);
+        assert_eq!(
+            hand::<JokersWild>("QJJQ2").hand_type(),
+            HandType::FourOfAKind
+        );
+        assert_eq!(
+            hand::<JokersWild>("JJJJJ").hand_type(),
+            HandType::FiveOfAKind
+        );
+        assert_eq!(hand::<DeucesWild>("QJJQ2").hand_type(), HandType::FullHouse);
+        assert_eq!(hand::<Standard>("23456").hand_type(), HandType::HighCard);
+    }
+
+    #[test]
+    fn test_explain() {
+        let (a, b) = (hand::<Standard>("KK677"), hand::<Standard>("KTJJT"));
+        assert!(a > b);
+        assert_eq!(
+            a.explain(&b),
+            Reason::Card {
+                position: 1,
+                cards: ('K', 'T')
+            }
+        );
+        assert_eq!(
+            b.explain(&a).to_string(),
+            "same type, card 2 is T against K"
+        );
+
+        let (a, b) = (hand::<JokersWild>("KTJJT"), hand::<JokersWild>("KK677"));
+        assert!(a > b);
+        assert_eq!(a.explain(&b).to_string(), "four of a kind against two pair");
+
+        // a lone joker is weaker than a 2
+        assert!(hand::<JokersWild>("JKKK2") < hand::<JokersWild>("2KKKJ"));
+        assert_eq!(
+            hand::<Standard>("AAAAA").explain(&hand("AAAAA")),
+            Reason::Identical
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "AAAA".parse::<Hand<Standard>>().unwrap_err(),
+            ParseHandError::Length(4)
+        );
+        assert_eq!(
+            "AAXAA".parse::<Hand<Standard>>().unwrap_err(),
+            ParseHandError::InvalidCard('X')
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+/// The network with nodes interned, `next[node][0]` going left and
+/// `next[node][1]` going right.
+#[derive(Debug)]
+pub struct Network<'a> {
+    instrs: Vec<usize>,
+    names: Vec<&'a str>,
+    next: Vec<[usize; 2]>,
+}
+
+/// Where a ghost stands at goal nodes. A walk over (node, instruction index)
+/// states always ends up in a cycle: the ghost is at a goal at the steps of
+/// `tail_goals`, then at every step `g + k * period` for `g` in `goals`.
+#[derive(Debug, PartialEq)]
+pub struct Ghost {
+    pub offset: u64,
+    pub period: u64,
+    pub tail_goals: Vec<u64>,
+    pub goals: Vec<u64>,
+}
+
+impl<'a> Network<'a> {
+    pub fn parse(input: &'a str) -> Self {
+        let mut lines = input.lines();
+
+        let instrs = lines
+            .next()
+            .unwrap()
+            .chars()
+            .map(|c| if c == 'L' { 0 } else { 1 })
+            .collect();
+        let nodes = lines
+            .skip(1)
+            .map(|l| (&l[0..3], &l[7..10], &l[12..15]))
+            .collect::<Vec<_>>();
+        let ids = nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| (node.0, id))
+            .collect::<HashMap<_, _>>();
+
+        Network {
+            instrs,
+            names: nodes.iter().map(|node| node.0).collect(),
+            next: nodes
+                .iter()
+                .map(|node| [ids[node.1], ids[node.2]])
+                .collect(),
+        }
+    }
+
+    pub fn ghost(&self, start: usize, is_goal: impl Fn(&str) -> bool) -> Ghost {
+        let len = self.instrs.len();
+        let mut seen = vec![None; self.names.len() * len];
+        let mut goals = Vec::new();
+        let mut node = start;
+        let mut step = 0;
+
+        let offset = loop {
+            let state = node * len + step % len;
+            if let Some(first) = seen[state] {
+                break first;
+            }
+            seen[state] = Some(step);
+
+            if is_goal(self.names[node]) {
+                goals.push(step as u64);
+            }
+            node = self.next[node][self.instrs[step % len]];
+            step += 1;
+        } as u64;
+
+        let (tail_goals, goals) = goals.into_iter().partition(|&g| g < offset);
+        Ghost {
+            offset,
+            period: step as u64 - offset,
+            tail_goals,
+            goals,
+        }
+    }
+
+    /// First step at which every ghost starting on a `is_start` node is on a
+    /// `is_goal` node.
+    pub fn solve(
+        &self,
+        is_start: impl Fn(&str) -> bool,
+        is_goal: impl Fn(&str) -> bool,
+    ) -> Option<u64> {
+        let ghosts = (0..self.names.len())
+            .filter(|&node| is_start(self.names[node]))
+            .map(|node| self.ghost(node, &is_goal))
+            .collect::<Vec<_>>();
+
+        synchronise(&ghosts)
+    }
+}
+
+impl Ghost {
+    pub fn at_goal(&self, step: u64) -> bool {
+        if step < self.offset {
+            self.tail_goals.contains(&step)
+        } else {
+            let step = self.offset + (step - self.offset) % self.period;
+            self.goals.contains(&step)
+        }
+    }
+}
+
+/// First step at which all ghosts are at a goal.
+pub fn synchronise(ghosts: &[Ghost]) -> Option<u64> {
+    let first = ghosts.first()?;
+    let max_offset = ghosts.iter().map(|g| g.offset).max().unwrap();
+
+    // before max_offset, some ghosts may not be in their cycle yet: check the
+    // first ghost goals one by one
+    let early = first
+        .tail_goals
+        .iter()
+        .copied()
+        .chain(
+            first
+                .goals
+                .iter()
+                .flat_map(|&g| (g..max_offset).step_by(first.period as usize)),
+        )
+        .filter(|&step| step < max_offset && ghosts.iter().all(|g| g.at_goal(step)))
+        .min();
+    if early.is_some() {
+        return early;
+    }
+
+    // afterwards, all ghosts cycle: solve step = goal (mod period) for every
+    // combination of goals with the chinese remainder theorem
+    let mut congruences = vec![(0i128, 1i128)];
+    for ghost in ghosts {
+        congruences = congruences
+            .iter()
+            .flat_map(|&(r, m)| {
+                ghost
+                    .goals
+                    .iter()
+                    .filter_map(move |&g| crt((r, m), (g as i128, ghost.period as i128)))
+            })
+            .collect();
+    }
+
+    congruences
+        .into_iter()
+        .map(|(r, m)| {
+            let max_offset = max_offset as i128;
+            if r >= max_offset {
+                r
+            } else {
+                r + (max_offset - r + m - 1) / m * m
+            }
+        })
+        .min()
+        .map(|step| step as u64)
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+/// Merge x = r1 (mod m1) and x = r2 (mod m2), moduli needn't be coprime.
+fn crt((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+
+    let lcm = m1 / g * m2;
+    let k = ((r2 - r1) / g * p).rem_euclid(m2 / g);
+    Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((0, 4), (2, 6)), Some((8, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+    }
+
+    #[test]
+    fn test_ghost() {
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)";
+        let network = Network::parse(input);
+        let ghost = network.ghost(0, |n| n.ends_with('Z'));
+        assert_eq!(
+            ghost,
+            Ghost {
+                offset: 1,
+                period: 2,
+                tail_goals: vec![],
+                goals: vec![2],
+            }
+        );
+        assert!(!ghost.at_goal(3));
+        assert!(ghost.at_goal(10));
+    }
+
+    #[test]
+    fn test_synchronise_with_offsets() {
+        // goals at even steps from 2 and at 1, 4, 7...: lcm(2, 3) isn't the answer
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)";
+        let network = Network::parse(input);
+        assert_eq!(
+            network.solve(|n| n.ends_with('A'), |n| n.ends_with('Z')),
+            Some(4)
+        );
+        assert_eq!(network.solve(|n| n == "22A", |n| n.ends_with('Z')), Some(1));
+        assert_eq!(network.solve(|n| n == "11A", |n| n == "22Z"), None);
+    }
+}
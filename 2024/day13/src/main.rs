@@ -1,4 +1,4 @@
-use regex::Regex;
+use utils::parse;
 
 #[derive(Debug)]
 struct Equation {
@@ -11,22 +11,9 @@ struct Equation {
 }
 
 fn parse(input: &str) -> Vec<Equation> {
-    fn numbers(line: &str) -> (i64, i64) {
-        let re = Regex::new(r"(\d+)[^\d]+(\d+)").unwrap();
-        let caps = re.captures(line).unwrap();
-        (
-            caps.get(1).unwrap().as_str().parse().unwrap(),
-            caps.get(2).unwrap().as_str().parse().unwrap(),
-        )
-    }
-
-    input
-        .split("\n\n")
+    parse::sections(input)
         .map(|game| {
-            let mut lines = game.lines();
-            let (ax, ay) = numbers(lines.next().unwrap());
-            let (bx, by) = numbers(lines.next().unwrap());
-            let (px, py) = numbers(lines.next().unwrap());
+            let [ax, ay, bx, by, px, py] = parse::integers_n(game).unwrap();
             Equation {
                 ax,
                 ay,
@@ -1,4 +1,5 @@
 use std::{cmp::Ordering, collections::HashMap};
+use utils::parse;
 
 #[derive(Debug)]
 struct Robot {
@@ -9,21 +10,16 @@ struct Robot {
 }
 
 fn parse(input: &str) -> Vec<Robot> {
-    input
-        .lines()
-        .filter_map(|line| {
-            line.split_once(' ').map(|(pos, velocity)| {
-                let (col, row) = pos[2..].split_once(',').unwrap();
-                let (v_col, v_row) = velocity[2..].split_once(',').unwrap();
-                Robot {
-                    row: row.parse().unwrap(),
-                    col: col.parse().unwrap(),
-                    velocity_row: v_row.parse().unwrap(),
-                    velocity_col: v_col.parse().unwrap(),
-                }
-            })
+    parse::lines(input.trim(), |line| {
+        let [col, row, velocity_col, velocity_row] = parse::integers_n(line)?;
+        Ok(Robot {
+            row,
+            col,
+            velocity_row,
+            velocity_col,
         })
-        .collect()
+    })
+    .unwrap()
 }
 
 fn wrap_index(idx: i32, max: i32) -> i32 {
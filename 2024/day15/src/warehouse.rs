@@ -0,0 +1,516 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn parse(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            '>' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Floor,
+    Wall,
+    /// Part of the box with this index
+    Box(usize),
+}
+
+/// A box covering `width` tiles to the right of `pos`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crate {
+    pub pos: (i32, i32),
+    pub width: i32,
+}
+
+impl Crate {
+    pub fn gps(&self) -> i32 {
+        100 * self.pos.0 + self.pos.1
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (0..self.width).map(|dc| (self.pos.0, self.pos.1 + dc))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownTile {
+        row: i32,
+        col: i32,
+        tile: char,
+    },
+    /// A `[` without its `]`, or the other way round
+    BrokenBox {
+        row: i32,
+        col: i32,
+    },
+    /// The row isn't as wide as the first one
+    RaggedRow {
+        row: i32,
+        expected: i32,
+        found: i32,
+    },
+    NoRobot,
+    UnknownMove(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownTile { row, col, tile } => {
+                write!(f, "unknown tile {tile:?} at {row},{col}")
+            }
+            ParseError::BrokenBox { row, col } => write!(f, "broken box at {row},{col}"),
+            ParseError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row} is {found} tiles wide instead of {expected}"),
+            ParseError::NoRobot => write!(f, "no robot"),
+            ParseError::UnknownMove(c) => write!(f, "unknown move {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantError {
+    /// A box tile doesn't point back to the box, or the box isn't whole.
+    BrokenBox(usize),
+    RobotNotOnFloor((i32, i32)),
+    /// More box tiles than the boxes cover
+    StrayTiles {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::BrokenBox(id) => write!(f, "box {id} is broken"),
+            InvariantError::RobotNotOnFloor((row, col)) => {
+                write!(f, "robot at {row},{col} isn't on the floor")
+            }
+            InvariantError::StrayTiles { expected, found } => {
+                write!(f, "{found} box tiles where boxes cover {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+/// What a move did: the boxes pushed, if the robot could move at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub dir: Direction,
+    pub robot: (i32, i32),
+    pub moved: bool,
+    pub pushed: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Warehouse {
+    rows: i32,
+    columns: i32,
+    tiles: Vec<Tile>,
+    boxes: Vec<Crate>,
+    robot: (i32, i32),
+    history: Vec<Step>,
+    undone: Vec<Direction>,
+}
+
+impl Warehouse {
+    /// Parse a map where boxes are either `O` or `[`, `=`..., `]`. Every tile
+    /// is first stretched `scale` times horizontally, `O` becoming a box as
+    /// wide.
+    pub fn parse(map: &str, scale: i32) -> Result<Self, ParseError> {
+        let mut tiles = Vec::new();
+        let mut boxes = Vec::new();
+        let mut robot = None;
+        let mut rows = 0;
+        let mut columns = 0;
+
+        for (row, line) in map.lines().enumerate() {
+            let row = row as i32;
+            let mut col = 0;
+            // start column of the box being read
+            let mut open = None;
+
+            for tile in line.chars() {
+                let (start, end) = (col, col + scale);
+                match tile {
+                    '#' => tiles.extend((start..end).map(|_| Tile::Wall)),
+                    '.' => tiles.extend((start..end).map(|_| Tile::Floor)),
+                    '@' => {
+                        robot = Some((row, start));
+                        tiles.extend((start..end).map(|_| Tile::Floor));
+                    }
+                    'O' => {
+                        tiles.extend((start..end).map(|_| Tile::Box(boxes.len())));
+                        boxes.push(Crate {
+                            pos: (row, start),
+                            width: scale,
+                        });
+                    }
+                    '[' if open.is_none() => open = Some(start),
+                    '=' if open.is_some() => {}
+                    ']' if open.is_some() => {
+                        let pos = (row, open.take().unwrap());
+                        tiles.extend((pos.1..end).map(|_| Tile::Box(boxes.len())));
+                        boxes.push(Crate {
+                            pos,
+                            width: end - pos.1,
+                        });
+                    }
+                    '[' | '=' | ']' => return Err(ParseError::BrokenBox { row, col }),
+                    tile => return Err(ParseError::UnknownTile { row, col, tile }),
+                }
+                if open.is_some() && tile != '[' && tile != '=' {
+                    return Err(ParseError::BrokenBox { row, col });
+                }
+                col = end;
+            }
+            if let Some(col) = open {
+                return Err(ParseError::BrokenBox { row, col });
+            }
+
+            if row > 0 && col != columns {
+                return Err(ParseError::RaggedRow {
+                    row,
+                    expected: columns,
+                    found: col,
+                });
+            }
+            rows += 1;
+            columns = col;
+        }
+
+        Ok(Warehouse {
+            rows,
+            columns,
+            tiles,
+            boxes,
+            robot: robot.ok_or(ParseError::NoRobot)?,
+            history: Vec::new(),
+            undone: Vec::new(),
+        })
+    }
+
+    #[cfg(test)]
+    pub fn boxes(&self) -> &[Crate] {
+        &self.boxes
+    }
+
+    pub fn tile(&self, (row, col): (i32, i32)) -> Tile {
+        if row < 0 || row >= self.rows || col < 0 || col >= self.columns {
+            Tile::Wall
+        } else {
+            self.tiles[(row * self.columns + col) as usize]
+        }
+    }
+
+    fn set_tile(&mut self, (row, col): (i32, i32), tile: Tile) {
+        self.tiles[(row * self.columns + col) as usize] = tile;
+    }
+
+    pub fn gps(&self) -> i32 {
+        self.boxes.iter().map(Crate::gps).sum()
+    }
+
+    /// Boxes pushed by the robot moving towards `dir`, or `None` if a wall
+    /// stops them.
+    fn pushed(&self, dir: Direction) -> Option<Vec<usize>> {
+        let (dr, dc) = dir.delta();
+        let mut pushed = Vec::new();
+        let mut front = vec![(self.robot.0 + dr, self.robot.1 + dc)];
+
+        while let Some(pos) = front.pop() {
+            match self.tile(pos) {
+                Tile::Wall => return None,
+                Tile::Floor => {}
+                Tile::Box(id) if pushed.contains(&id) => {}
+                Tile::Box(id) => {
+                    pushed.push(id);
+                    let b = self.boxes[id];
+                    match dir {
+                        Direction::Left => front.push((b.pos.0, b.pos.1 - 1)),
+                        Direction::Right => front.push((b.pos.0, b.pos.1 + b.width)),
+                        Direction::Up | Direction::Down => {
+                            front.extend(b.cells().map(|(row, col)| (row + dr, col)))
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(pushed)
+    }
+
+    fn shift(&mut self, ids: &[usize], dir: Direction) {
+        let (dr, dc) = dir.delta();
+        for &id in ids {
+            for pos in self.boxes[id].cells().collect::<Vec<_>>() {
+                self.set_tile(pos, Tile::Floor);
+            }
+        }
+        for &id in ids {
+            let b = &mut self.boxes[id];
+            b.pos = (b.pos.0 + dr, b.pos.1 + dc);
+            for pos in self.boxes[id].cells().collect::<Vec<_>>() {
+                self.set_tile(pos, Tile::Box(id));
+            }
+        }
+    }
+
+    fn apply(&mut self, dir: Direction) -> Step {
+        let robot = self.robot;
+        let step = match self.pushed(dir) {
+            Some(pushed) => {
+                self.shift(&pushed, dir);
+                let (dr, dc) = dir.delta();
+                self.robot = (robot.0 + dr, robot.1 + dc);
+                Step {
+                    dir,
+                    robot,
+                    moved: true,
+                    pushed,
+                }
+            }
+            None => Step {
+                dir,
+                robot,
+                moved: false,
+                pushed: Vec::new(),
+            },
+        };
+        self.history.push(step.clone());
+
+        step
+    }
+
+    /// Move the robot, pushing the boxes in front of it.
+    pub fn step(&mut self, dir: Direction) -> Step {
+        self.undone.clear();
+        self.apply(dir)
+    }
+
+    pub fn run(&mut self, moves: &[Direction]) {
+        moves.iter().for_each(|&dir| {
+            self.step(dir);
+        });
+    }
+
+    /// Cancel the last step, returning it.
+    pub fn undo(&mut self) -> Option<Step> {
+        let step = self.history.pop()?;
+        self.shift(&step.pushed, step.dir.opposite());
+        self.robot = step.robot;
+        self.undone.push(step.dir);
+
+        Some(step)
+    }
+
+    /// Replay the last undone step.
+    pub fn redo(&mut self) -> Option<Step> {
+        let dir = self.undone.pop()?;
+        Some(self.apply(dir))
+    }
+
+    /// Check that every box is whole and the robot on the floor.
+    pub fn check(&self) -> Result<(), InvariantError> {
+        for (id, b) in self.boxes.iter().enumerate() {
+            if b.width < 1 || b.cells().any(|pos| self.tile(pos) != Tile::Box(id)) {
+                return Err(InvariantError::BrokenBox(id));
+            }
+        }
+
+        let expected = self.boxes.iter().map(|b| b.width as usize).sum();
+        let found = self
+            .tiles
+            .iter()
+            .filter(|t| matches!(t, Tile::Box(_)))
+            .count();
+        if expected != found {
+            return Err(InvariantError::StrayTiles { expected, found });
+        }
+
+        if self.tile(self.robot) != Tile::Floor {
+            return Err(InvariantError::RobotNotOnFloor(self.robot));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Warehouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows {
+            for col in 0..self.columns {
+                let c = match self.tile((row, col)) {
+                    _ if (row, col) == self.robot => '@',
+                    Tile::Floor => '.',
+                    Tile::Wall => '#',
+                    Tile::Box(id) => {
+                        let b = self.boxes[id];
+                        match (b.width, col - b.pos.1) {
+                            (1, _) => 'O',
+                            (_, 0) => '[',
+                            (w, dc) if dc == w - 1 => ']',
+                            _ => '=',
+                        }
+                    }
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn parse_moves(moves: &str) -> Result<Vec<Direction>, ParseError> {
+    moves
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| Direction::parse(c).ok_or(ParseError::UnknownMove(c)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######";
+
+    #[test]
+    fn test_scaled_push() {
+        let mut warehouse = Warehouse::parse(MAP, 2).unwrap();
+        assert_eq!(warehouse.to_string().lines().nth(3), Some("##....[][]@.##"));
+
+        let step = warehouse.step(Direction::Left);
+        assert!(step.moved);
+        assert_eq!(step.pushed, vec![1, 0]);
+        warehouse.check().unwrap();
+
+        for dir in parse_moves("vv<<^").unwrap() {
+            warehouse.step(dir);
+        }
+        // the last move pushes a box up, and both boxes resting on it
+        assert_eq!(
+            warehouse.to_string(),
+            "##############
+##......##..##
+##...[][]...##
+##....[]....##
+##.....@....##
+##..........##
+##############
+"
+        );
+        warehouse.check().unwrap();
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut warehouse = Warehouse::parse(MAP, 1).unwrap();
+        let initial = warehouse.to_string();
+        let moves = parse_moves("<<^<v").unwrap();
+        warehouse.run(&moves);
+        let after = warehouse.to_string();
+
+        while warehouse.undo().is_some() {}
+        assert_eq!(warehouse.to_string(), initial);
+        while warehouse.redo().is_some() {}
+        assert_eq!(warehouse.to_string(), after);
+
+        warehouse.undo();
+        warehouse.step(Direction::Up);
+        assert_eq!(warehouse.redo(), None);
+        warehouse.check().unwrap();
+    }
+
+    #[test]
+    fn test_wide_boxes() {
+        let mut warehouse =
+            Warehouse::parse("#######\n#.....#\n#.[=].#\n#..@..#\n#######", 1).unwrap();
+        assert_eq!(
+            warehouse.boxes(),
+            &[Crate {
+                pos: (2, 2),
+                width: 3
+            }]
+        );
+
+        let step = warehouse.step(Direction::Up);
+        assert_eq!(step.pushed, vec![0]);
+        assert_eq!(warehouse.gps(), 102);
+        // blocked by the wall
+        assert!(!warehouse.step(Direction::Up).moved);
+        warehouse.check().unwrap();
+    }
+
+    #[test]
+    fn test_invariants() {
+        assert_eq!(
+            Warehouse::parse("#[.]@#", 1).unwrap_err(),
+            ParseError::BrokenBox { row: 0, col: 2 }
+        );
+        assert_eq!(
+            Warehouse::parse("#.]@#", 1).unwrap_err(),
+            ParseError::BrokenBox { row: 0, col: 2 }
+        );
+        assert_eq!(
+            Warehouse::parse("#####\n#.@.#\n###", 2).unwrap_err(),
+            ParseError::RaggedRow {
+                row: 2,
+                expected: 10,
+                found: 6
+            }
+        );
+
+        let mut warehouse = Warehouse::parse("#[]@#", 1).unwrap();
+        warehouse.set_tile((0, 2), Tile::Floor);
+        assert_eq!(warehouse.check(), Err(InvariantError::BrokenBox(0)));
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+};
+
+use utils::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    fn coords(self) -> (i32, i32) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+
+    fn turn_right(self) -> Self {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    fn turn_left(self) -> Self {
+        Direction::ALL[(self as usize + 3) % 4]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Forward,
+    TurnLeft,
+    TurnRight,
+}
+
+/// Cost of each move, the puzzle's being 1 per step and 1000 per quarter turn.
+/// Both must be positive for `Maze::solve`.
+#[derive(Clone, Copy, Debug)]
+pub struct Costs {
+    pub forward: usize,
+    pub turn: usize,
+}
+
+impl Default for Costs {
+    fn default() -> Self {
+        Costs {
+            forward: 1,
+            turn: 1000,
+        }
+    }
+}
+
+pub struct Maze {
+    grid: Grid<char>,
+    start: (i32, i32),
+    end: (i32, i32),
+}
+
+/// A state is a cell and a direction, indexed as `cell * 4 + direction`.
+type State = usize;
+
+/// Every optimal route from the start to the end, as a DAG where each state
+/// links back to the states reaching it with the lowest score.
+#[derive(Debug)]
+pub struct Solution {
+    pub score: usize,
+    columns: i32,
+    start: State,
+    ends: Vec<State>,
+    preds: Vec<Vec<State>>,
+}
+
+impl Maze {
+    pub fn parse(input: &str) -> Option<Self> {
+        let grid = Grid::<char>::parse(input);
+        let find = |tile| {
+            grid.position_iter()
+                .find(|&(row, col)| grid.get(row, col) == Some(tile))
+        };
+        let (start, end) = (find('S')?, find('E')?);
+
+        Some(Maze { grid, start, end })
+    }
+
+    fn state(&self, (row, col): (i32, i32), dir: Direction) -> State {
+        (row * self.grid.columns + col) as usize * 4 + dir as usize
+    }
+
+    fn moves(&self, state: State, costs: Costs) -> impl Iterator<Item = (State, usize)> + '_ {
+        let cell = state as i32 / 4;
+        let (row, col) = (cell / self.grid.columns, cell % self.grid.columns);
+        let dir = Direction::ALL[state % 4];
+        let (dr, dc) = dir.coords();
+        let forward = (row + dr, col + dc);
+
+        let forward = self
+            .grid
+            .get(forward.0, forward.1)
+            .is_some_and(|c| c != '#')
+            .then(|| (self.state(forward, dir), costs.forward));
+        let turns = [dir.turn_left(), dir.turn_right()]
+            .map(|dir| (self.state((row, col), dir), costs.turn));
+
+        forward.into_iter().chain(turns)
+    }
+
+    /// Dijkstra from the start facing east, reaching the end in any direction.
+    /// `None` if there is no route, or if a cost is zero as the routes could
+    /// then go round in circles.
+    pub fn solve(&self, costs: Costs) -> Option<Solution> {
+        if costs.forward == 0 || costs.turn == 0 {
+            return None;
+        }
+        let states = (self.grid.rows * self.grid.columns) as usize * 4;
+        let mut scores = vec![usize::MAX; states];
+        let mut preds = vec![Vec::new(); states];
+
+        let start = self.state(self.start, Direction::East);
+        scores[start] = 0;
+        let mut frontier = BinaryHeap::from([Reverse((0, start))]);
+
+        while let Some(Reverse((score, state))) = frontier.pop() {
+            if score > scores[state] {
+                continue;
+            }
+            for (next, cost) in self.moves(state, costs) {
+                let next_score = score + cost;
+                if next_score < scores[next] {
+                    scores[next] = next_score;
+                    preds[next] = vec![state];
+                    frontier.push(Reverse((next_score, next)));
+                } else if next_score == scores[next] {
+                    preds[next].push(state);
+                }
+            }
+        }
+
+        let ends = Direction::ALL.map(|dir| self.state(self.end, dir));
+        let score = ends.iter().map(|&end| scores[end]).min()?;
+        if score == usize::MAX {
+            return None;
+        }
+
+        Some(Solution {
+            score,
+            columns: self.grid.columns,
+            start,
+            ends: ends
+                .into_iter()
+                .filter(|&end| scores[end] == score)
+                .collect(),
+            preds,
+        })
+    }
+}
+
+impl Solution {
+    fn cell(&self, state: State) -> (i32, i32) {
+        let cell = state as i32 / 4;
+        (cell / self.columns, cell % self.columns)
+    }
+
+    /// Cells on at least one optimal route.
+    pub fn tiles(&self) -> HashSet<(i32, i32)> {
+        let mut seen = HashSet::new();
+        let mut todo = self.ends.clone();
+
+        while let Some(state) = todo.pop() {
+            if seen.insert(state) {
+                todo.extend(&self.preds[state]);
+            }
+        }
+
+        seen.into_iter().map(|state| self.cell(state)).collect()
+    }
+
+    /// Number of optimal routes, without listing them.
+    pub fn count_routes(&self) -> usize {
+        fn count(solution: &Solution, state: State, memo: &mut Vec<Option<usize>>) -> usize {
+            if state == solution.start {
+                return 1;
+            }
+            if let Some(n) = memo[state] {
+                return n;
+            }
+            let n = solution.preds[state]
+                .iter()
+                .map(|&pred| count(solution, pred, memo))
+                .sum();
+            memo[state] = Some(n);
+            n
+        }
+
+        let mut memo = vec![None; self.preds.len()];
+        self.ends
+            .iter()
+            .map(|&end| count(self, end, &mut memo))
+            .sum()
+    }
+
+    /// Every optimal route as the moves from the start. There may be
+    /// exponentially many of them, see `count_routes` first.
+    pub fn routes(&self) -> Vec<Vec<Move>> {
+        fn walk(
+            solution: &Solution,
+            state: State,
+            suffix: &mut Vec<Move>,
+            routes: &mut Vec<Vec<Move>>,
+        ) {
+            if state == solution.start {
+                routes.push(suffix.iter().rev().copied().collect());
+                return;
+            }
+
+            for &pred in &solution.preds[state] {
+                let m = if pred / 4 != state / 4 {
+                    Move::Forward
+                } else if Direction::ALL[pred % 4].turn_left() == Direction::ALL[state % 4] {
+                    Move::TurnLeft
+                } else {
+                    Move::TurnRight
+                };
+                suffix.push(m);
+                walk(solution, pred, suffix, routes);
+                suffix.pop();
+            }
+        }
+
+        let mut routes = Vec::new();
+        for &end in &self.ends {
+            walk(self, end, &mut Vec::new(), &mut routes);
+        }
+
+        routes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        let maze = Maze::parse("#####\n#...#\n#S#E#\n#...#\n#####").unwrap();
+        let solution = maze.solve(Costs::default()).unwrap();
+        // both ways around the wall
+        assert_eq!(solution.score, 3 * 1000 + 4);
+        assert_eq!(solution.count_routes(), 2);
+
+        use Move::*;
+        let mut routes = solution.routes();
+        routes.sort_by_key(|r| format!("{r:?}"));
+        assert_eq!(
+            routes,
+            vec![
+                vec![TurnLeft, Forward, TurnRight, Forward, Forward, TurnRight, Forward],
+                vec![TurnRight, Forward, TurnLeft, Forward, Forward, TurnLeft, Forward],
+            ]
+        );
+        assert_eq!(solution.tiles().len(), 8);
+    }
+
+    #[test]
+    fn test_costs() {
+        // east is a wall: turning around costs two turns
+        let maze = Maze::parse("#####\n#E.S#\n#####").unwrap();
+        let solution = maze
+            .solve(Costs {
+                forward: 5,
+                turn: 1,
+            })
+            .unwrap();
+        assert_eq!(solution.score, 2 + 10);
+        assert_eq!(solution.count_routes(), 2);
+
+        assert!(Maze::parse("#####\n#S#E#\n#####")
+            .unwrap()
+            .solve(Costs::default())
+            .is_none());
+        // free turns would let the routes spin on the spot
+        assert!(maze
+            .solve(Costs {
+                forward: 1,
+                turn: 0,
+            })
+            .is_none());
+    }
+}
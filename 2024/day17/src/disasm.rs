@@ -0,0 +1,236 @@
+use std::fmt;
+
+use crate::{Instr, Opcode};
+
+fn combo(operand: usize) -> String {
+    match operand {
+        0..=3 => operand.to_string(),
+        4 => "a".to_string(),
+        5 => "b".to_string(),
+        6 => "c".to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+fn mnemonic(opcode: Opcode) -> &'static str {
+    match opcode {
+        Opcode::Adv => "adv",
+        Opcode::Bxl => "bxl",
+        Opcode::Bst => "bst",
+        Opcode::Jnz => "jnz",
+        Opcode::Bxc => "bxc",
+        Opcode::Out => "out",
+        Opcode::Bdv => "bdv",
+        Opcode::Cdv => "cdv",
+    }
+}
+
+/// One line of pseudo-code per instruction, with combo operands resolved:
+///
+/// ```text
+///  0: adv 3    a = a >> 3
+///  2: out a    out a & 7
+///  4: jnz 0    if a != 0 goto 0
+/// ```
+pub fn disassemble(instrs: &[Instr]) -> String {
+    instrs
+        .iter()
+        .step_by(2)
+        .enumerate()
+        .map(|(i, instr)| {
+            let (operand, code) = match instr.opcode {
+                Opcode::Adv => (
+                    combo(instr.operand),
+                    format!("a = a >> {}", combo(instr.operand)),
+                ),
+                Opcode::Bxl => (
+                    instr.operand.to_string(),
+                    format!("b = b ^ {}", instr.operand),
+                ),
+                Opcode::Bst => (
+                    combo(instr.operand),
+                    format!("b = {} & 7", combo(instr.operand)),
+                ),
+                Opcode::Jnz => (
+                    instr.operand.to_string(),
+                    format!("if a != 0 goto {}", instr.operand),
+                ),
+                Opcode::Bxc => ("-".to_string(), "b = b ^ c".to_string()),
+                Opcode::Out => (
+                    combo(instr.operand),
+                    format!("out {} & 7", combo(instr.operand)),
+                ),
+                Opcode::Bdv => (
+                    combo(instr.operand),
+                    format!("b = a >> {}", combo(instr.operand)),
+                ),
+                Opcode::Cdv => (
+                    combo(instr.operand),
+                    format!("c = a >> {}", combo(instr.operand)),
+                ),
+            };
+            format!(
+                "{:2}: {} {:<4} {}\n",
+                2 * i,
+                mnemonic(instr.opcode),
+                operand,
+                code
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    InvalidOperand { addr: usize },
+    NotALoop,
+    ExtraJump { addr: usize },
+    ShiftCount(usize),
+    UnsupportedShift { addr: usize },
+    OutputCount(usize),
+    CarriedRegister { reg: char, addr: usize },
+    NoSolution,
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::InvalidOperand { addr } => {
+                write!(f, "reserved combo operand 7 at address {addr}")
+            }
+            AnalysisError::NotALoop => {
+                write!(f, "program doesn't end with a jnz 0 back to the start")
+            }
+            AnalysisError::ExtraJump { addr } => {
+                write!(f, "jump at address {addr} besides the final jnz 0")
+            }
+            AnalysisError::ShiftCount(n) => {
+                write!(f, "expected exactly one adv per loop, found {n}")
+            }
+            AnalysisError::UnsupportedShift { addr } => {
+                write!(f, "adv at address {addr} doesn't shift a by 3")
+            }
+            AnalysisError::OutputCount(n) => {
+                write!(f, "expected exactly one out per loop, found {n}")
+            }
+            AnalysisError::CarriedRegister { reg, addr } => write!(
+                f,
+                "register {reg} is read at address {addr} before being set in the loop"
+            ),
+            AnalysisError::NoSolution => write!(f, "no value of a makes the program output itself"),
+        }
+    }
+}
+
+/// Check that the program is a single loop which consumes a three bits per
+/// iteration and outputs one value computed from a alone. Then the n-th output
+/// only depends on `a >> 3n`, which is what the part2 search relies on.
+pub fn analyze(instrs: &[Instr]) -> Result<(), AnalysisError> {
+    let body = instrs.iter().step_by(2).collect::<Vec<_>>();
+
+    match body.last() {
+        Some(Instr {
+            opcode: Opcode::Jnz,
+            operand: 0,
+        }) if instrs.len() % 2 == 1 => {}
+        _ => return Err(AnalysisError::NotALoop),
+    }
+
+    let (mut shifts, mut outputs) = (0, 0);
+    let (mut b_set, mut c_set) = (false, false);
+
+    for (i, instr) in body[..body.len() - 1].iter().enumerate() {
+        let addr = 2 * i;
+        let uses_combo = !matches!(instr.opcode, Opcode::Bxl | Opcode::Jnz | Opcode::Bxc);
+        if uses_combo {
+            match instr.operand {
+                5 if !b_set => return Err(AnalysisError::CarriedRegister { reg: 'b', addr }),
+                6 if !c_set => return Err(AnalysisError::CarriedRegister { reg: 'c', addr }),
+                7 => return Err(AnalysisError::InvalidOperand { addr }),
+                _ => {}
+            }
+        }
+
+        match instr.opcode {
+            Opcode::Adv => {
+                shifts += 1;
+                if instr.operand != 3 {
+                    return Err(AnalysisError::UnsupportedShift { addr });
+                }
+            }
+            Opcode::Bxl if !b_set => return Err(AnalysisError::CarriedRegister { reg: 'b', addr }),
+            Opcode::Bxc if !b_set => return Err(AnalysisError::CarriedRegister { reg: 'b', addr }),
+            Opcode::Bxc if !c_set => return Err(AnalysisError::CarriedRegister { reg: 'c', addr }),
+            Opcode::Bst | Opcode::Bdv => b_set = true,
+            Opcode::Cdv => c_set = true,
+            Opcode::Jnz => return Err(AnalysisError::ExtraJump { addr }),
+            Opcode::Out => outputs += 1,
+            Opcode::Bxl | Opcode::Bxc => {}
+        }
+    }
+
+    if shifts != 1 {
+        return Err(AnalysisError::ShiftCount(shifts));
+    }
+    if outputs != 1 {
+        return Err(AnalysisError::OutputCount(outputs));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+
+    #[test]
+    fn test_disassemble() {
+        let instrs = decode(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(
+            disassemble(&instrs),
+            " 0: adv 3    a = a >> 3
+ 2: out a    out a & 7
+ 4: jnz 0    if a != 0 goto 0
+"
+        );
+
+        let instrs = decode(&[2, 4, 1, 1, 7, 5, 4, 0]);
+        assert_eq!(
+            disassemble(&instrs),
+            " 0: bst a    b = a & 7
+ 2: bxl 1    b = b ^ 1
+ 4: cdv b    c = a >> b
+ 6: bxc -    b = b ^ c
+"
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let ok = [2, 4, 1, 1, 7, 5, 0, 3, 4, 3, 1, 6, 5, 5, 3, 0];
+        assert_eq!(analyze(&decode(&ok)), Ok(()));
+        assert_eq!(analyze(&decode(&[0, 3, 5, 4, 3, 0])), Ok(()));
+
+        assert_eq!(
+            analyze(&decode(&[0, 1, 5, 4, 3, 0])),
+            Err(AnalysisError::UnsupportedShift { addr: 0 })
+        );
+        assert_eq!(
+            analyze(&decode(&[0, 3, 5, 4, 3, 2])),
+            Err(AnalysisError::NotALoop)
+        );
+        assert_eq!(
+            analyze(&decode(&[0, 3, 5, 4, 5, 4, 3, 0])),
+            Err(AnalysisError::OutputCount(2))
+        );
+        assert_eq!(
+            analyze(&decode(&[1, 1, 0, 3, 5, 5, 3, 0])),
+            Err(AnalysisError::CarriedRegister { reg: 'b', addr: 0 })
+        );
+        assert_eq!(
+            analyze(&decode(&[5, 4, 3, 0])),
+            Err(AnalysisError::ShiftCount(0))
+        );
+    }
+}
@@ -1,3 +1,11 @@
+mod disasm;
+
+use disasm::{analyze, disassemble, AnalysisError};
+use utils::{
+    parse,
+    vm::{InstructionSet, Machine, Step},
+};
+
 #[derive(Debug, Clone, Copy)]
 enum Opcode {
     Adv,
@@ -48,133 +56,166 @@ struct Regs {
     a: usize,
     b: usize,
     c: usize,
-    ip: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct Instr {
     opcode: Opcode,
     operand: usize,
 }
 
-fn run(instrs: &[Instr], regs: &mut Regs, stop_at_out: bool) -> Vec<usize> {
-    let mut output = Vec::new();
-
-    while regs.ip < instrs.len() {
-        let instr = &instrs[regs.ip];
-        let combo = match instr.operand {
-            value @ 0..=3 => value,
-            4 => regs.a,
-            5 => regs.b,
-            6 => regs.c,
-            _ => unreachable!(),
+struct Chronospatial;
+
+impl InstructionSet for Chronospatial {
+    type Instr = Instr;
+    type Regs = Regs;
+    type Output = usize;
+
+    const WIDTH: usize = 2;
+
+    fn execute(&self, instr: &Instr, regs: &mut Regs) -> Step<usize> {
+        // only some instructions read a combo operand, 7 being invalid there
+        let combo = || match instr.operand {
+            value @ 0..=3 => Some(value),
+            4 => Some(regs.a),
+            5 => Some(regs.b),
+            6 => Some(regs.c),
+            _ => None,
+        };
+        let shift = |a: usize| {
+            let combo = combo()?;
+            Some(u32::try_from(combo).map_or(0, |n| a.checked_shr(n).unwrap_or(0)))
         };
 
         match instr.opcode {
-            Opcode::Adv => regs.a /= 2usize.pow(combo as u32),
+            Opcode::Adv => match shift(regs.a) {
+                Some(a) => regs.a = a,
+                None => return Step::Fault,
+            },
             Opcode::Bxl => regs.b ^= instr.operand,
-            Opcode::Bst => regs.b = combo % 8,
+            Opcode::Bst => match combo() {
+                Some(combo) => regs.b = combo % 8,
+                None => return Step::Fault,
+            },
             Opcode::Jnz => {
                 if regs.a != 0 {
-                    regs.ip = 0;
-                    continue;
+                    return Step::Jump(instr.operand);
                 }
             }
             Opcode::Bxc => regs.b ^= regs.c,
-            Opcode::Out => {
-                output.push(combo % 8);
-                if stop_at_out {
-                    break;
-                }
-            }
-            Opcode::Bdv => regs.b = regs.a / 2usize.pow(combo as u32),
-            Opcode::Cdv => regs.c = regs.a / 2usize.pow(combo as u32),
+            Opcode::Out => match combo() {
+                Some(combo) => return Step::Output(combo % 8),
+                None => return Step::Fault,
+            },
+            Opcode::Bdv => match shift(regs.a) {
+                Some(b) => regs.b = b,
+                None => return Step::Fault,
+            },
+            Opcode::Cdv => match shift(regs.a) {
+                Some(c) => regs.c = c,
+                None => return Step::Fault,
+            },
         }
 
-        regs.ip += 1;
+        Step::Next
     }
+}
 
-    output
+// Jumps can land on any address, so decode an instruction at every one of
+// them: the machine then moves 2 addresses at a time.
+fn decode(program: &[usize]) -> Vec<Instr> {
+    program
+        .windows(2)
+        .map(|w| Instr {
+            opcode: Opcode::try_from(w[0]).unwrap(),
+            operand: w[1],
+        })
+        .collect()
 }
 
-fn parse(input: &str) -> (Regs, Vec<Instr>) {
-    let (regs, instrs) = input.split_once("\n\n").unwrap();
+fn parse(input: &str) -> (Regs, Vec<usize>) {
+    let [regs, program] = parse::sections_n(input).unwrap();
 
     let mut regs = regs.lines();
-    let a = regs.next().unwrap()[12..].parse().unwrap();
-    let b = regs.next().unwrap()[12..].parse().unwrap();
-    let c = regs.next().unwrap()[12..].parse().unwrap();
-    let regs = Regs { a, b, c, ip: 0 };
-
-    let instrs = instrs[9..]
-        .trim_end()
-        .split(',')
-        .collect::<Vec<_>>()
-        .chunks(2)
-        .map(|c| Instr {
-            opcode: Opcode::try_from(c[0].parse::<usize>().unwrap()).unwrap(),
-            operand: c[1].parse().unwrap(),
-        })
-        .collect();
+    let mut reg =
+        |name| parse::number(parse::prefixed(regs.next().unwrap(), name).unwrap()).unwrap();
+    let regs = Regs {
+        a: reg("Register A: "),
+        b: reg("Register B: "),
+        c: reg("Register C: "),
+    };
+
+    let program = parse::list(parse::prefixed(program, "Program: ").unwrap(), ',').unwrap();
 
-    (regs, instrs)
+    (regs, program)
 }
 
 fn part1(input: &str) -> String {
-    let (mut regs, instrs) = parse(input);
-    let output = run(&instrs, &mut regs, false);
+    let (regs, program) = parse(input);
+    let mut machine = Machine::new(Chronospatial, decode(&program), regs);
+    machine.run();
 
-    output
+    machine
+        .output()
         .iter()
         .map(|n| n.to_string())
         .collect::<Vec<_>>()
         .join(",")
 }
 
-fn dfs(instrs: &[Instr], a: usize, program: &[usize]) -> usize {
-    if program.is_empty() {
-        return a;
+fn dfs(
+    machine: &mut Machine<Chronospatial>,
+    a: usize,
+    program: &[usize],
+    rest: usize,
+) -> Option<usize> {
+    if rest == 0 {
+        // leading zero bits could shorten the run: check the whole output
+        machine.reset(Regs { a, b: 0, c: 0 });
+        machine.run();
+        return (machine.output() == program).then_some(a);
     }
 
-    for delta in 0..8 {
+    (0..8).find_map(|delta| {
         let new_a = (a << 3) | delta;
-        let mut regs = Regs {
+        machine.reset(Regs {
             a: new_a,
             b: 0,
             c: 0,
-            ip: 0,
-        };
-        let output = run(instrs, &mut regs, true);
-        if output.first() == program.last() {
-            let result = dfs(instrs, new_a, &program[..program.len() - 1]);
-
-            if result != 0 {
-                return result;
-            }
+        });
+        machine.run_until_output();
+        if machine.output().first() == Some(&program[rest - 1]) {
+            dfs(machine, new_a, program, rest - 1)
+        } else {
+            None
         }
-    }
-
-    0
+    })
 }
 
-fn part2(input: &str) -> usize {
-    let (_, instrs) = parse(input);
-    let program = instrs
-        .iter()
-        .flat_map(|instr| [instr.opcode.into(), instr.operand])
-        .collect::<Vec<_>>();
+fn part2(input: &str) -> Result<usize, AnalysisError> {
+    let (regs, program) = parse(input);
+    let instrs = decode(&program);
+    analyze(&instrs)?;
 
-    dfs(&instrs, 0, &program)
+    let mut machine = Machine::new(Chronospatial, instrs, regs);
+    dfs(&mut machine, 0, &program, program.len()).ok_or(AnalysisError::NoSolution)
 }
 
 fn main() {
     println!("part1={}", part1(include_str!("../input.txt")));
-    println!("part2={}", part2(include_str!("../input.txt")));
+    match part2(include_str!("../input.txt")) {
+        Ok(a) => println!("part2={a}"),
+        Err(e) => {
+            let (_, program) = parse(include_str!("../input.txt"));
+            println!("part2: {e}\n{}", disassemble(&decode(&program)));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use utils::vm::Exit;
 
     #[test]
     fn test_part1() {
@@ -186,6 +227,48 @@ Program: 0,1,5,4,3,0";
         assert_eq!(part1(input), "4,6,3,5,6,3,5,2,1,0");
     }
 
+    #[test]
+    fn test_part1_jump_target() {
+        // out B once, then loop on: adv 1, out A, jnz 2
+        let input = "Register A: 8
+Register B: 9
+Register C: 0
+
+Program: 5,5,0,1,5,4,3,2";
+        assert_eq!(part1(input), "1,4,2,1,0");
+    }
+
+    #[test]
+    fn test_operand_7() {
+        // bxl 7, out B, jnz 7: literal operands can be 7
+        let input = "Register A: 1
+Register B: 2
+Register C: 0
+
+Program: 1,7,5,5,3,7";
+        let (regs, program) = parse(input);
+        let mut machine = Machine::new(Chronospatial, decode(&program), regs);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine.output(), &[5]);
+
+        // but not combo ones: out 7
+        let (regs, program) = parse(&input.replace("3,7", "5,7"));
+        let mut machine = Machine::new(Chronospatial, decode(&program), regs);
+        assert_eq!(machine.run(), Exit::Fault(4));
+        assert_eq!(machine.output(), &[5]);
+    }
+
+    #[test]
+    fn test_large_shift() {
+        // adv A, out A with A above 64
+        let input = "Register A: 100
+Register B: 0
+Register C: 0
+
+Program: 0,4,5,4";
+        assert_eq!(part1(input), "0");
+    }
+
     #[test]
     fn test_part2() {
         let input = "Register A: 2024
@@ -193,6 +276,19 @@ Register B: 0
 Register C: 0
 
 Program: 0,3,5,4,3,0";
-        assert_eq!(part2(input), 117440);
+        assert_eq!(part2(input), Ok(117440));
+    }
+
+    #[test]
+    fn test_part2_unsupported() {
+        let input = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+        assert_eq!(
+            part2(input),
+            Err(AnalysisError::UnsupportedShift { addr: 0 })
+        );
     }
 }
@@ -1,4 +1,5 @@
 use std::{cmp::Ordering, collections::HashSet};
+use utils::parse;
 
 #[derive(Debug)]
 struct Game {
@@ -7,20 +8,17 @@ struct Game {
 }
 
 fn parse(input: &str) -> Game {
-    let (rules, updates) = input.split_once("\n\n").unwrap();
+    let [rules, updates] = parse::sections_n(input).unwrap();
 
-    let rules = rules
-        .lines()
-        .map(|line| {
-            let (before, after) = line.split_once('|').unwrap();
-            (before.parse().unwrap(), after.parse().unwrap())
-        })
-        .collect();
+    let rules = parse::lines(rules, |line| {
+        let (before, after) = parse::split_once(line, "|")?;
+        Ok((parse::number(before)?, parse::number(after)?))
+    })
+    .unwrap()
+    .into_iter()
+    .collect();
 
-    let updates = updates
-        .lines()
-        .map(|line| line.split(',').map(|page| page.parse().unwrap()).collect())
-        .collect();
+    let updates = parse::lines(updates, |line| parse::list(line, ',')).unwrap();
 
     Game { rules, updates }
 }
@@ -1,129 +1,38 @@
-use std::collections::HashSet;
+mod patrol;
 
+use patrol::Patrol;
 use utils::Grid;
 
-struct Game {
-    grid: Grid<char>,
-    starting_pos: (i32, i32),
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Top,
-    Right,
-    Bottom,
-    Left,
-}
-
-fn parse(input: &str) -> Game {
-    let mut columns = 0;
-    let grid = input
-        .lines()
-        .flat_map(|line| {
-            columns = line.len() as i32;
-            line.chars()
-        })
-        .collect::<Vec<_>>();
-    let rows = grid.len() as i32 / columns;
-
-    let grid = Grid {
-        grid,
-        rows,
-        columns,
-    };
-
-    let starting_pos = grid
-        .position_iter()
-        .find(|(row, col)| grid.get(*row, *col).unwrap() == '^')
-        .expect("can't find the starting position");
-
-    Game { grid, starting_pos }
-}
-
-enum Solution {
-    Visited(HashSet<(i32, i32)>),
-    Loop(bool),
-}
-
-fn visit(game: &Game, detect_loop: bool) -> Solution {
-    let mut direction = Direction::Top;
-    let grid = &game.grid;
-    let (mut row, mut col) = game.starting_pos;
-
-    let mut visited = HashSet::from([game.starting_pos]);
-    let mut visit_direction = HashSet::from([(game.starting_pos, direction)]);
-    loop {
-        let (next_row, next_col) = match direction {
-            Direction::Top if row >= 1 => (row - 1, col),
-            Direction::Right if col < grid.columns - 1 => (row, col + 1),
-            Direction::Bottom if row < grid.rows - 1 => (row + 1, col),
-            Direction::Left if col >= 1 => (row, col - 1),
-            _ => break,
-        };
-
-        match grid.get(next_row, next_col) {
-            Some('#') => {
-                direction = match direction {
-                    Direction::Top => Direction::Right,
-                    Direction::Right => Direction::Bottom,
-                    Direction::Bottom => Direction::Left,
-                    Direction::Left => Direction::Top,
-                };
-                if detect_loop {
-                    if visit_direction.contains(&((row, col), direction)) {
-                        return Solution::Loop(true);
-                    }
-                    visit_direction.insert(((row, col), direction));
-                }
-            }
-            Some('.' | '^') => {
-                row = next_row;
-                col = next_col;
-                if !detect_loop {
-                    visited.insert((row, col));
-                }
-            }
-            _ => break,
-        }
-    }
-
-    if detect_loop {
-        Solution::Loop(false)
-    } else {
-        Solution::Visited(visited)
-    }
+fn parse(input: &str) -> Patrol {
+    Patrol::new(&Grid::<char>::parse(input)).expect("can't find the starting position")
 }
 
 fn part1(input: &str) -> usize {
-    let game = parse(input);
-    if let Solution::Visited(visited) = visit(&game, false) {
-        visited.len()
-    } else {
-        unreachable!()
-    }
+    parse(input).walk().len()
 }
 
 fn part2(input: &str) -> usize {
-    let mut game = parse(input);
-    let Solution::Visited(visited) = visit(&game, false) else {
-        unreachable!()
-    };
+    parse(input).loop_obstructions().len()
+}
 
-    let mut loops = 0;
-    for (row, col) in visited {
-        game.grid.set(row, col, '#').unwrap();
-        if let Solution::Loop(is_loop) = visit(&game, true) {
-            if is_loop {
-                loops += 1;
+fn main() {
+    // cargo run -- --loop 6,3
+    let args = std::env::args().collect::<Vec<_>>();
+    if let [_, flag, blocked] = &args[..] {
+        if flag == "--loop" {
+            let patrol = parse(include_str!("../input.txt"));
+            let (row, col) = blocked.split_once(',').expect("expected row,col");
+            let blocked = (row.parse().unwrap(), col.parse().unwrap());
+            if patrol.loops_with(blocked) {
+                let cells = patrol.loop_with(blocked).unwrap();
+                print!("{}", patrol.render(Some(blocked), &cells));
+            } else {
+                println!("no loop with an obstacle at {blocked:?}");
             }
+            return;
         }
-        game.grid.set(row, col, '.').unwrap();
     }
 
-    loops
-}
-
-fn main() {
     println!("part1={}", part1(include_str!("../input.txt")));
     println!("part2={}", part2(include_str!("../input.txt")));
 }
@@ -150,4 +59,35 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(INPUT), 6);
     }
+
+    #[test]
+    fn test_blocked_queries() {
+        let patrol = parse(INPUT);
+        let mut obstructions = patrol.loop_obstructions();
+        obstructions.sort();
+        assert_eq!(
+            obstructions,
+            vec![(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]
+        );
+        assert!(obstructions.iter().all(|&pos| patrol.loops_with(pos)));
+        assert!(!patrol.loops_with((0, 0)));
+        assert!(patrol.loop_with((0, 0)).is_none());
+        // outside of the lab, past where the guard leaves it, or on the guard
+        for blocked in [(-1, 4), (10, 7), (11, 7), (3, -2), (6, 4)] {
+            assert!(!patrol.loops_with(blocked), "{blocked:?}");
+            assert!(patrol.loop_with(blocked).is_none(), "{blocked:?}");
+        }
+
+        // the first example loop: a rectangle with the guard's start on its side
+        let cells = patrol.loop_with((6, 3)).unwrap();
+        assert_eq!(cells.len(), 18);
+        assert!(cells.contains(&(6, 4)));
+        assert!(cells
+            .iter()
+            .all(|&(row, col)| row == 1 || row == 6 || col == 4 || col == 8));
+        assert_eq!(
+            patrol.render(Some((6, 3)), &cells).lines().nth(6),
+            Some(".#.O^XXXX.")
+        );
+    }
 }
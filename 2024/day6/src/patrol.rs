@@ -0,0 +1,267 @@
+use utils::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_right(self) -> Self {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+}
+
+type Pos = (i32, i32);
+
+/// The lab and the guard. For each cell and direction, `jumps` holds the cell
+/// where the guard stops in front of an obstacle, or `None` if she leaves the
+/// lab, so that a whole straight line is walked at once.
+#[derive(Debug)]
+pub struct Patrol {
+    rows: i32,
+    columns: i32,
+    obstacles: Vec<bool>,
+    start: Pos,
+    jumps: [Vec<Option<Pos>>; 4],
+}
+
+impl Patrol {
+    pub fn new(grid: &Grid<char>) -> Option<Self> {
+        let start = grid
+            .position_iter()
+            .find(|&(row, col)| grid.get(row, col) == Some('^'))?;
+        let obstacles = grid.grid.iter().map(|&c| c == '#').collect::<Vec<_>>();
+
+        let mut patrol = Patrol {
+            rows: grid.rows,
+            columns: grid.columns,
+            obstacles,
+            start,
+            jumps: Default::default(),
+        };
+        patrol.jumps = Direction::ALL.map(|dir| patrol.jump_table(dir));
+
+        Some(patrol)
+    }
+
+    fn index(&self, (row, col): Pos) -> Option<usize> {
+        (row >= 0 && row < self.rows && col >= 0 && col < self.columns)
+            .then(|| (row * self.columns + col) as usize)
+    }
+
+    fn jump_table(&self, dir: Direction) -> Vec<Option<Pos>> {
+        let (dr, dc) = dir.delta();
+        let mut jumps = vec![None; self.obstacles.len()];
+
+        // the next cell must be computed first: walk against the direction
+        let mut cells = (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |col| (row, col)))
+            .collect::<Vec<_>>();
+        if matches!(dir, Direction::Down | Direction::Right) {
+            cells.reverse();
+        }
+
+        for (row, col) in cells {
+            let idx = self.index((row, col)).unwrap();
+            let next = (row + dr, col + dc);
+            jumps[idx] = match self.index(next) {
+                None => None,
+                Some(next_idx) if self.obstacles[next_idx] => Some((row, col)),
+                Some(next_idx) => jumps[next_idx],
+            };
+        }
+
+        jumps
+    }
+
+    pub fn is_blocked(&self, pos: Pos) -> bool {
+        self.index(pos).is_some_and(|idx| self.obstacles[idx])
+    }
+
+    /// Where the guard stops walking from `pos` towards `dir`, with an extra
+    /// obstacle at `blocked`.
+    fn jump(&self, pos: Pos, dir: Direction, blocked: Option<Pos>) -> Option<Pos> {
+        let target = self.jumps[dir as usize][self.index(pos).unwrap()];
+        let Some(blocked) = blocked else {
+            return target;
+        };
+
+        // distance to `blocked` if it is ahead of the guard
+        let (dr, dc) = dir.delta();
+        let ahead = match dir {
+            Direction::Up | Direction::Down if blocked.1 == pos.1 => (blocked.0 - pos.0) * dr,
+            Direction::Left | Direction::Right if blocked.0 == pos.0 => (blocked.1 - pos.1) * dc,
+            _ => 0,
+        };
+        let before_target = match target {
+            None => true,
+            Some(t) => ahead <= (t.0 - pos.0).abs() + (t.1 - pos.1).abs(),
+        };
+
+        if ahead > 0 && before_target {
+            Some((blocked.0 - dr, blocked.1 - dc))
+        } else {
+            target
+        }
+    }
+
+    /// Follow the guard from `pos` until she leaves or loops, returning the
+    /// state she loops on.
+    fn find_loop(
+        &self,
+        mut pos: Pos,
+        mut dir: Direction,
+        blocked: Option<Pos>,
+    ) -> Option<(Pos, Direction)> {
+        // directions the guard turned in at each cell, as bits
+        let mut turns = vec![0u8; self.obstacles.len()];
+
+        loop {
+            pos = self.jump(pos, dir, blocked)?;
+            let idx = self.index(pos).unwrap();
+            let bit = 1 << dir as u8;
+            if turns[idx] & bit != 0 {
+                return Some((pos, dir));
+            }
+            turns[idx] |= bit;
+            dir = dir.turn_right();
+        }
+    }
+
+    /// Cells in the order the guard first walks on them, with the direction
+    /// she enters them from.
+    pub fn walk(&self) -> Vec<(Pos, Direction)> {
+        let mut first = vec![false; self.obstacles.len()];
+        let mut turns = vec![0u8; self.obstacles.len()];
+        let (mut pos, mut dir) = (self.start, Direction::Up);
+        let mut walk = vec![(pos, dir)];
+        first[self.index(pos).unwrap()] = true;
+
+        loop {
+            let (dr, dc) = dir.delta();
+            let next = (pos.0 + dr, pos.1 + dc);
+            let Some(idx) = self.index(next) else {
+                break;
+            };
+
+            if self.obstacles[idx] {
+                let bit = 1 << dir as u8;
+                let turned = &mut turns[self.index(pos).unwrap()];
+                if *turned & bit != 0 {
+                    break;
+                }
+                *turned |= bit;
+                dir = dir.turn_right();
+            } else {
+                pos = next;
+                if !first[idx] {
+                    first[idx] = true;
+                    walk.push((pos, dir));
+                }
+            }
+        }
+
+        walk
+    }
+
+    /// Whether `blocked` is a cell an obstacle can be added on: in the lab,
+    /// but not where the guard starts.
+    fn can_block(&self, blocked: Pos) -> bool {
+        self.index(blocked).is_some() && blocked != self.start
+    }
+
+    /// Whether the guard loops if `blocked` were an obstacle, false if it
+    /// can't be one.
+    pub fn loops_with(&self, blocked: Pos) -> bool {
+        self.can_block(blocked)
+            && self
+                .find_loop(self.start, Direction::Up, Some(blocked))
+                .is_some()
+    }
+
+    /// Cells of the loop the guard ends up in if `blocked` were an obstacle,
+    /// in walking order. `None` if she leaves or `blocked` can't be an
+    /// obstacle.
+    pub fn loop_with(&self, blocked: Pos) -> Option<Vec<Pos>> {
+        if !self.can_block(blocked) {
+            return None;
+        }
+        let state = self.find_loop(self.start, Direction::Up, Some(blocked))?;
+        let (mut pos, mut dir) = state;
+        let mut cells = Vec::new();
+
+        loop {
+            dir = dir.turn_right();
+            let target = self.jump(pos, dir, Some(blocked)).unwrap();
+            let (dr, dc) = dir.delta();
+            while pos != target {
+                pos = (pos.0 + dr, pos.1 + dc);
+                cells.push(pos);
+            }
+            if (pos, dir) == state {
+                break;
+            }
+        }
+
+        Some(cells)
+    }
+
+    /// The lab with `blocked` drawn as `O` and the cells of `path` as `X`.
+    pub fn render(&self, blocked: Option<Pos>, path: &[Pos]) -> String {
+        let mut lab = String::new();
+        for row in 0..self.rows {
+            for col in 0..self.columns {
+                let pos = (row, col);
+                lab.push(if Some(pos) == blocked {
+                    'O'
+                } else if self.is_blocked(pos) {
+                    '#'
+                } else if pos == self.start {
+                    '^'
+                } else if path.contains(&pos) {
+                    'X'
+                } else {
+                    '.'
+                });
+            }
+            lab.push('\n');
+        }
+
+        lab
+    }
+
+    /// Cells where an obstacle makes the guard loop. The guard walks the same
+    /// way until she first reaches the new obstacle, so each check starts
+    /// right in front of it.
+    pub fn loop_obstructions(&self) -> Vec<Pos> {
+        self.walk()
+            .into_iter()
+            .skip(1)
+            .filter(|&(pos, dir)| {
+                let (dr, dc) = dir.delta();
+                let before = (pos.0 - dr, pos.1 - dc);
+                self.find_loop(before, dir, Some(pos)).is_some()
+            })
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+}
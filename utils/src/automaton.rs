@@ -0,0 +1,231 @@
+use crate::Grid;
+
+/// How cells evolve at each step. A step first computes every cell from the
+/// previous generation with `update`, then lets firing cells propagate to their
+/// neighbours until nothing fires anymore. A cell fires at most once per step.
+pub trait Rule {
+    type Cell: Copy + PartialEq;
+
+    /// New value of the cell at (`row`, `col`), `grid` being the previous
+    /// generation.
+    fn update(&self, grid: &Grid<Self::Cell>, row: i32, col: i32) -> Self::Cell;
+
+    /// Cells affected by the cell at (`row`, `col`), the 8 surrounding ones by
+    /// default.
+    fn neighbors(&self, grid: &Grid<Self::Cell>, row: i32, col: i32) -> Vec<(i32, i32)> {
+        grid.neighbors_all(row, col).collect()
+    }
+
+    fn fires(&self, _cell: &Self::Cell) -> bool {
+        false
+    }
+
+    /// Effect of a firing cell on a neighbour which hasn't fired yet.
+    fn propagate(&self, _cell: &mut Self::Cell) {}
+
+    /// Value of a cell which fired, at the end of the step.
+    fn settle(&self, _cell: &mut Self::Cell) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of the step, starting at 1
+    pub step: usize,
+    pub fired: usize,
+    pub changed: usize,
+}
+
+#[derive(Debug)]
+pub struct Automaton<R: Rule> {
+    pub rule: R,
+    grid: Grid<R::Cell>,
+    steps: usize,
+}
+
+impl<R: Rule> Automaton<R> {
+    pub fn new(rule: R, grid: Grid<R::Cell>) -> Self {
+        Automaton {
+            rule,
+            grid,
+            steps: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<R::Cell> {
+        &self.grid
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn step(&mut self) -> Stats {
+        let next = self
+            .grid
+            .position_iter()
+            .map(|(row, col)| self.rule.update(&self.grid, row, col))
+            .collect();
+        let previous = std::mem::replace(&mut self.grid.grid, next);
+
+        let columns = self.grid.columns;
+        let mut fired = vec![false; previous.len()];
+        let mut stack = Vec::new();
+        for (idx, cell) in self.grid.grid.iter().enumerate() {
+            if self.rule.fires(cell) {
+                fired[idx] = true;
+                stack.push(idx);
+            }
+        }
+
+        while let Some(idx) = stack.pop() {
+            let (row, col) = (idx as i32 / columns, idx as i32 % columns);
+            for (row, col) in self.rule.neighbors(&self.grid, row, col) {
+                let idx = (row * columns + col) as usize;
+                if fired[idx] {
+                    continue;
+                }
+                let cell = &mut self.grid.grid[idx];
+                self.rule.propagate(cell);
+                if self.rule.fires(cell) {
+                    fired[idx] = true;
+                    stack.push(idx);
+                }
+            }
+        }
+
+        for (cell, _) in self.grid.grid.iter_mut().zip(&fired).filter(|(_, f)| **f) {
+            self.rule.settle(cell);
+        }
+
+        self.steps += 1;
+        Stats {
+            step: self.steps,
+            fired: fired.iter().filter(|&&f| f).count(),
+            changed: previous
+                .iter()
+                .zip(&self.grid.grid)
+                .filter(|(a, b)| a != b)
+                .count(),
+        }
+    }
+
+    /// Statistics of the next `steps` steps.
+    pub fn run(&mut self, steps: usize) -> Vec<Stats> {
+        (0..steps).map(|_| self.step()).collect()
+    }
+
+    /// Step until `done` holds after a step, giving up after `limit` steps.
+    pub fn run_until(
+        &mut self,
+        limit: usize,
+        mut done: impl FnMut(&Stats, &Grid<R::Cell>) -> bool,
+    ) -> Option<Stats> {
+        for _ in 0..limit {
+            let stats = self.step();
+            if done(&stats, &self.grid) {
+                return Some(stats);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Life;
+
+    impl Rule for Life {
+        type Cell = bool;
+
+        fn update(&self, grid: &Grid<bool>, row: i32, col: i32) -> bool {
+            let alive = grid
+                .neighbors_all(row, col)
+                .filter(|&(row, col)| grid.get(row, col).unwrap())
+                .count();
+            matches!((grid.get(row, col).unwrap(), alive), (true, 2) | (_, 3))
+        }
+    }
+
+    // cells gain 1 per step and fire at 3, giving 1 to their cross neighbours
+    struct Chain;
+
+    impl Rule for Chain {
+        type Cell = u8;
+
+        fn update(&self, grid: &Grid<u8>, row: i32, col: i32) -> u8 {
+            grid.get(row, col).unwrap() + 1
+        }
+
+        fn neighbors(&self, grid: &Grid<u8>, row: i32, col: i32) -> Vec<(i32, i32)> {
+            grid.neighbors_cross(row, col).collect()
+        }
+
+        fn fires(&self, cell: &u8) -> bool {
+            *cell >= 3
+        }
+
+        fn propagate(&self, cell: &mut u8) {
+            *cell += 1;
+        }
+
+        fn settle(&self, cell: &mut u8) {
+            *cell = 0;
+        }
+    }
+
+    #[test]
+    fn test_blinker() {
+        let grid = Grid {
+            grid: vec![false, true, false, false, true, false, false, true, false],
+            rows: 3,
+            columns: 3,
+        };
+        let mut life = Automaton::new(Life, grid);
+
+        let stats = life.step();
+        assert_eq!(
+            life.grid().grid,
+            vec![false, false, false, true, true, true, false, false, false]
+        );
+        assert_eq!(
+            stats,
+            Stats {
+                step: 1,
+                fired: 0,
+                changed: 4
+            }
+        );
+        life.step();
+        assert_eq!(life.grid().row(1), Some(vec![false, true, false]));
+    }
+
+    #[test]
+    fn test_cascade() {
+        let grid = Grid {
+            grid: vec![2, 1, 1, 1, 0],
+            rows: 1,
+            columns: 5,
+        };
+        let mut chain = Automaton::new(Chain, grid);
+
+        // 3 2 2 2 1: the first cell fires, then the next ones one by one
+        let stats = chain.step();
+        assert_eq!(chain.grid().grid, vec![0, 0, 0, 0, 2]);
+        assert_eq!(stats.fired, 4);
+
+        // 1 1 1 1 3: only the last one fires
+        assert_eq!(chain.run(1)[0].fired, 1);
+        assert_eq!(chain.grid().grid, vec![1, 1, 1, 2, 0]);
+        assert_eq!(chain.steps(), 2);
+
+        // 2 2 2 3 1: the fourth cell fires towards the left
+        let stats = chain.run_until(10, |stats, _| stats.fired == 4).unwrap();
+        assert_eq!(stats.step, 3);
+        assert_eq!(chain.grid().grid, vec![0, 0, 0, 0, 2]);
+        assert_eq!(chain.run_until(10, |_, grid| grid.grid[0] == 42), None);
+        assert_eq!(chain.steps(), 13);
+    }
+}
@@ -0,0 +1,377 @@
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn step(self, direction: Direction, distance: i64) -> Self {
+        self + direction.delta() * distance
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: i64) -> Point {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// The 8 compass directions, `y` growing downwards like grid rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn delta(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+            Direction::Right => Point::new(1, 0),
+            Direction::UpLeft => Point::new(-1, -1),
+            Direction::UpRight => Point::new(1, -1),
+            Direction::DownLeft => Point::new(-1, 1),
+            Direction::DownRight => Point::new(1, 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Self {
+        Segment { a, b }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.a.y == self.b.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.a.x == self.b.x
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        (self.a.x - self.b.x).abs() == (self.a.y - self.b.y).abs()
+    }
+
+    /// Number of lattice points on the segment, both ends included.
+    pub fn lattice_points(&self) -> i64 {
+        let d = self.b - self.a;
+        gcd(d.x.abs(), d.y.abs()) + 1
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        let (ab, ap) = (self.b - self.a, p - self.a);
+        ab.x * ap.y - ab.y * ap.x == 0
+            && p.x >= self.a.x.min(self.b.x)
+            && p.x <= self.a.x.max(self.b.x)
+            && p.y >= self.a.y.min(self.b.y)
+            && p.y <= self.a.y.max(self.b.y)
+    }
+
+    /// Rasterise the segment from `a` to `b`, both included. Only horizontal,
+    /// vertical and 45° diagonal segments can be rasterised.
+    pub fn points(&self) -> Option<SegmentIter> {
+        if self.is_horizontal() || self.is_vertical() || self.is_diagonal() {
+            Some(SegmentIter {
+                curr: Some(self.a),
+                end: self.b,
+                delta: Point::new(
+                    (self.b.x - self.a.x).signum(),
+                    (self.b.y - self.a.y).signum(),
+                ),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+pub struct SegmentIter {
+    curr: Option<Point>,
+    end: Point,
+    delta: Point,
+}
+
+impl Iterator for SegmentIter {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.curr?;
+
+        if curr == self.end {
+            self.curr.take()
+        } else {
+            self.curr.replace(curr + self.delta)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+/// A simple polygon with integer vertices. Consecutive vertices are joined by
+/// an edge and the last vertex is joined back to the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(mut vertices: Vec<Point>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        Polygon { vertices }
+    }
+
+    /// Build a polygon by walking from `start`, e.g. a dig plan.
+    pub fn from_moves(start: Point, moves: impl IntoIterator<Item = (Direction, i64)>) -> Self {
+        let mut p = start;
+        let vertices = std::iter::once(start)
+            .chain(moves.into_iter().map(|(direction, distance)| {
+                p = p.step(direction, distance);
+                p
+            }))
+            .collect();
+
+        Polygon::new(vertices)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| Segment::new(self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Shoelace formula: twice the signed area, positive when the vertices
+    /// turn counterclockwise in a `y` up frame.
+    pub fn signed_double_area(&self) -> i64 {
+        self.edges().map(|e| e.a.x * e.b.y - e.b.x * e.a.y).sum()
+    }
+
+    pub fn double_area(&self) -> i64 {
+        self.signed_double_area().abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.double_area() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|e| {
+                let d = e.b - e.a;
+                ((d.x * d.x + d.y * d.y) as f64).sqrt()
+            })
+            .sum()
+    }
+
+    /// Number of lattice points lying on the edges.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|e| e.lattice_points() - 1).sum()
+    }
+
+    /// Number of lattice points strictly inside, from Pick's theorem:
+    /// A = i + b/2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// Interior and boundary lattice points.
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    // https://en.wikipedia.org/wiki/Point_in_polygon
+    pub fn locate(&self, p: Point) -> Location {
+        let mut inside = false;
+
+        for e in self.edges() {
+            if e.contains(p) {
+                return Location::Boundary;
+            }
+
+            let (a, b) = (e.a, e.b);
+            if (a.y > p.y) != (b.y > p.y) {
+                // sign of the cross product tells on which side of the edge p lies
+                let cross = (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y);
+                if (cross > 0) == (b.y > a.y) {
+                    inside = !inside;
+                }
+            }
+        }
+
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.locate(p) != Location::Outside
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Location, Point, Polygon, Segment};
+
+    fn square(side: i64) -> Polygon {
+        Polygon::new(vec![
+            Point::new(0, 0),
+            Point::new(side, 0),
+            Point::new(side, side),
+            Point::new(0, side),
+        ])
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        let polygon = square(4);
+        assert_eq!(polygon.double_area(), 32);
+        assert_eq!(polygon.area(), 16.0);
+        assert_eq!(polygon.perimeter(), 16.0);
+
+        let triangle = Polygon::new(vec![Point::new(0, 0), Point::new(3, 0), Point::new(0, 3)]);
+        assert_eq!(triangle.area(), 4.5);
+    }
+
+    #[test]
+    fn test_polygon_pick() {
+        let polygon = square(4);
+        assert_eq!(polygon.boundary_points(), 16);
+        assert_eq!(polygon.interior_points(), 9);
+        assert_eq!(polygon.lattice_points(), 25);
+
+        let triangle = Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(0, 4)]);
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.interior_points(), 3);
+    }
+
+    #[test]
+    fn test_polygon_from_moves() {
+        let polygon = Polygon::from_moves(
+            Point::new(0, 0),
+            [
+                (Direction::Right, 2),
+                (Direction::Down, 2),
+                (Direction::Left, 2),
+                (Direction::Up, 2),
+            ],
+        );
+        assert_eq!(polygon.vertices().len(), 4);
+        assert_eq!(polygon.lattice_points(), 9);
+    }
+
+    #[test]
+    fn test_polygon_locate() {
+        // U shape
+        let polygon = Polygon::new(vec![
+            Point::new(0, 0),
+            Point::new(2, 0),
+            Point::new(2, 4),
+            Point::new(4, 4),
+            Point::new(4, 0),
+            Point::new(6, 0),
+            Point::new(6, 6),
+            Point::new(0, 6),
+        ]);
+        assert_eq!(polygon.locate(Point::new(1, 1)), Location::Inside);
+        assert_eq!(polygon.locate(Point::new(3, 2)), Location::Outside);
+        assert_eq!(polygon.locate(Point::new(3, 5)), Location::Inside);
+        assert_eq!(polygon.locate(Point::new(3, 4)), Location::Boundary);
+        assert_eq!(polygon.locate(Point::new(0, 3)), Location::Boundary);
+        assert_eq!(polygon.locate(Point::new(7, 0)), Location::Outside);
+        assert_eq!(polygon.locate(Point::new(-1, 4)), Location::Outside);
+    }
+
+    #[test]
+    fn test_segment_points() {
+        let points = |a, b| Segment::new(a, b).points().map(|it| it.collect::<Vec<_>>());
+
+        assert_eq!(
+            points(Point::new(0, 0), Point::new(2, 0)),
+            Some(vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)])
+        );
+        assert_eq!(
+            points(Point::new(1, 3), Point::new(1, 1)),
+            Some(vec![Point::new(1, 3), Point::new(1, 2), Point::new(1, 1)])
+        );
+        assert_eq!(
+            points(Point::new(2, 0), Point::new(0, 2)),
+            Some(vec![Point::new(2, 0), Point::new(1, 1), Point::new(0, 2)])
+        );
+        assert_eq!(
+            points(Point::new(0, 0), Point::new(0, 0)),
+            Some(vec![Point::new(0, 0)])
+        );
+        assert_eq!(points(Point::new(0, 0), Point::new(1, 2)), None);
+
+        for direction in Direction::ALL {
+            let end = Point::new(0, 0).step(direction, 3);
+            assert_eq!(points(Point::new(0, 0), end).unwrap().len(), 4);
+        }
+    }
+}
@@ -1,3 +1,8 @@
+pub mod automaton;
+pub mod geometry;
+pub mod parse;
+pub mod vm;
+
 use std::ops::Range;
 
 #[derive(Debug)]
@@ -214,6 +219,21 @@ where
         .into_iter()
         .filter(|&(row, col)| row >= 0 && row < self.rows && col >= 0 && col < self.columns)
     }
+
+    pub fn neighbors_all(&self, row: i32, col: i32) -> impl Iterator<Item = (i32, i32)> {
+        [
+            (row - 1, col - 1),
+            (row - 1, col),
+            (row - 1, col + 1),
+            (row, col - 1),
+            (row, col + 1),
+            (row + 1, col - 1),
+            (row + 1, col),
+            (row + 1, col + 1),
+        ]
+        .into_iter()
+        .filter(|&(row, col)| row >= 0 && row < self.rows && col >= 0 && col < self.columns)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,264 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    MissingDelimiter {
+        delimiter: String,
+        input: String,
+    },
+    InvalidNumber {
+        input: String,
+    },
+    WrongCount {
+        expected: usize,
+        found: usize,
+        input: String,
+    },
+    Unexpected {
+        expected: &'static str,
+        input: String,
+    },
+    AtLine {
+        line: usize,
+        source: Box<ParseError>,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingDelimiter { delimiter, input } => {
+                write!(f, "missing delimiter {delimiter:?} in {input:?}")
+            }
+            ParseError::InvalidNumber { input } => write!(f, "invalid number {input:?}"),
+            ParseError::WrongCount {
+                expected,
+                found,
+                input,
+            } => write!(f, "expected {expected} items, found {found} in {input:?}"),
+            ParseError::Unexpected { expected, input } => {
+                write!(f, "expected {expected}, found {input:?}")
+            }
+            ParseError::AtLine { line, source } => write!(f, "line {line}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::AtLine { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Split the input on blank lines. Leading/trailing blank lines and runs of
+/// several blank lines don't produce empty sections.
+pub fn sections(input: &str) -> impl Iterator<Item = &str> {
+    let mut rest = input;
+
+    std::iter::from_fn(move || {
+        let mut start = None;
+        let mut end = 0;
+        let mut offset = 0;
+
+        for line in rest.split_inclusive('\n') {
+            let blank = line.trim().is_empty();
+            match (start, blank) {
+                (None, false) => {
+                    start = Some(offset);
+                    end = offset + line.trim_end_matches(['\n', '\r']).len();
+                }
+                (Some(_), false) => end = offset + line.trim_end_matches(['\n', '\r']).len(),
+                (Some(_), true) => break,
+                (None, true) => {}
+            }
+            offset += line.len();
+        }
+
+        let section = &rest[start?..end];
+        rest = &rest[end..];
+        Some(section)
+    })
+}
+
+/// Exactly `N` blank-line separated sections.
+pub fn sections_n<const N: usize>(input: &str) -> Result<[&str; N]> {
+    collect_n(sections(input), input)
+}
+
+/// Extract every integer from `s`, ignoring whatever is around them. A `-`
+/// is a sign only if it isn't preceded by a digit, so `1-3` yields 1 and 3.
+pub fn integers<T: FromStr>(s: &str) -> Result<Vec<T>> {
+    let bytes = s.as_bytes();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+        if negative || bytes[i].is_ascii_digit() {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            numbers.push(number(&s[start..i])?);
+        } else {
+            i += 1;
+        }
+    }
+
+    Ok(numbers)
+}
+
+/// Exactly `N` integers, e.g. `p=0,4 v=3,-3`.
+pub fn integers_n<T: FromStr, const N: usize>(s: &str) -> Result<[T; N]> {
+    collect_n(integers(s)?.into_iter(), s)
+}
+
+pub fn number<T: FromStr>(s: &str) -> Result<T> {
+    s.trim().parse().map_err(|_| ParseError::InvalidNumber {
+        input: s.to_string(),
+    })
+}
+
+/// Parse every item of `s` split on `delimiter`, e.g. `75,47,61`. Empty
+/// items are skipped so that whitespace runs behave.
+pub fn list<T: FromStr>(s: &str, delimiter: char) -> Result<Vec<T>> {
+    s.split(delimiter)
+        .filter(|item| !item.trim().is_empty())
+        .map(number)
+        .collect()
+}
+
+/// Whitespace separated list, e.g. `41 48 83 86 17`.
+pub fn words<T: FromStr>(s: &str) -> Result<Vec<T>> {
+    s.split_whitespace().map(number).collect()
+}
+
+pub fn split_once<'a>(s: &'a str, delimiter: &str) -> Result<(&'a str, &'a str)> {
+    s.split_once(delimiter)
+        .ok_or_else(|| ParseError::MissingDelimiter {
+            delimiter: delimiter.to_string(),
+            input: s.to_string(),
+        })
+}
+
+/// Split `key: value`, both sides trimmed.
+pub fn key_value(s: &str) -> Result<(&str, &str)> {
+    split_once(s, ":").map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// Parse `key: a b c` lines into the key and its values.
+pub fn labelled_list<T: FromStr>(s: &str) -> Result<(&str, Vec<T>)> {
+    let (key, values) = key_value(s)?;
+    Ok((key, words(values)?))
+}
+
+/// Strip a literal prefix, e.g. `Register A: `.
+pub fn prefixed<'a>(s: &'a str, prefix: &'static str) -> Result<&'a str> {
+    s.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::Unexpected {
+            expected: prefix,
+            input: s.to_string(),
+        })
+}
+
+/// Map each line with `f`, tagging errors with their (1-based) line number.
+pub fn lines<'a, T>(input: &'a str, mut f: impl FnMut(&'a str) -> Result<T>) -> Result<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            f(line).map_err(|e| ParseError::AtLine {
+                line: i + 1,
+                source: Box::new(e),
+            })
+        })
+        .collect()
+}
+
+fn collect_n<T, const N: usize>(items: impl Iterator<Item = T>, input: &str) -> Result<[T; N]> {
+    let items = items.collect::<Vec<_>>();
+    let found = items.len();
+    items.try_into().map_err(|_| ParseError::WrongCount {
+        expected: N,
+        found,
+        input: input.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let input = "\n47|53\n97|13\n\n\n75,47,61\r\n\r\n97,61\n\n";
+        assert_eq!(
+            sections(input).collect::<Vec<_>>(),
+            vec!["47|53\n97|13", "75,47,61", "97,61"]
+        );
+        assert_eq!(sections("").count(), 0);
+
+        let [rules, updates] = sections_n::<2>("1|2\n\n1,2").unwrap();
+        assert_eq!((rules, updates), ("1|2", "1,2"));
+        assert!(matches!(
+            sections_n::<3>("1|2\n\n1,2"),
+            Err(ParseError::WrongCount {
+                expected: 3,
+                found: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(integers::<i32>("p=0,4 v=3,-3").unwrap(), vec![0, 4, 3, -3]);
+        assert_eq!(
+            integers::<i64>("Button A: X+94, Y+34").unwrap(),
+            vec![94, 34]
+        );
+        assert_eq!(integers::<u32>("1-3 a").unwrap(), vec![1, 3]);
+        assert_eq!(integers::<i32>("-").unwrap(), vec![]);
+        assert_eq!(integers_n::<i32, 2>("x=-5, y=12").unwrap(), [-5, 12]);
+        assert!(matches!(
+            integers::<u8>("x=300"),
+            Err(ParseError::InvalidNumber { .. })
+        ));
+        assert!(matches!(
+            integers::<u32>("x=-3"),
+            Err(ParseError::InvalidNumber { .. })
+        ));
+    }
+
+    #[test]
+    fn test_combinators() {
+        assert_eq!(
+            labelled_list::<u32>("Card 1: 41 48  83").unwrap(),
+            ("Card 1", vec![41, 48, 83])
+        );
+        assert_eq!(list::<u32>("75,47,61", ',').unwrap(), vec![75, 47, 61]);
+        assert_eq!(prefixed("Register A: 729", "Register A: "), Ok("729"));
+        assert!(matches!(
+            split_once("47-53", "|"),
+            Err(ParseError::MissingDelimiter { .. })
+        ));
+
+        let err = lines("1|2\n3-4", |line| {
+            let (a, b) = split_once(line, "|")?;
+            Ok((number::<u32>(a)?, number::<u32>(b)?))
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: missing delimiter \"|\" in \"3-4\""
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashSet, fmt::Debug};
+
+/// What the machine should do after executing an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<O> {
+    Next,
+    Jump(usize),
+    Output(O),
+    Halt,
+    /// The instruction can't be executed, the machine stops on it.
+    Fault,
+}
+
+/// Why the machine stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Halted,
+    Output,
+    Breakpoint(usize),
+    StepLimit,
+    Fault(usize),
+}
+
+/// An instruction set: how to execute one decoded instruction against the
+/// registers. Addresses are indexes into the program, an instruction taking
+/// `WIDTH` addresses.
+pub trait InstructionSet {
+    type Instr: Clone + Debug;
+    type Regs: Clone + Debug;
+    type Output: Clone + Debug;
+
+    const WIDTH: usize = 1;
+
+    fn execute(&self, instr: &Self::Instr, regs: &mut Self::Regs) -> Step<Self::Output>;
+}
+
+#[derive(Debug, Clone)]
+pub struct Trace<I: InstructionSet> {
+    pub ip: usize,
+    pub instr: I::Instr,
+    pub regs: I::Regs,
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine<I: InstructionSet> {
+    pub isa: I,
+    pub program: Vec<I::Instr>,
+    pub regs: I::Regs,
+    pub ip: usize,
+    pub steps: usize,
+    step_limit: Option<usize>,
+    breakpoints: HashSet<usize>,
+    trace: Option<Vec<Trace<I>>>,
+    output: Vec<I::Output>,
+}
+
+impl<I: InstructionSet> Machine<I> {
+    pub fn new(isa: I, program: Vec<I::Instr>, regs: I::Regs) -> Self {
+        Machine {
+            isa,
+            program,
+            regs,
+            ip: 0,
+            steps: 0,
+            step_limit: None,
+            breakpoints: HashSet::new(),
+            trace: None,
+            output: Vec::new(),
+        }
+    }
+
+    /// Stop with `Exit::StepLimit` once `limit` instructions were executed.
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Record every executed instruction along with the registers after it.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    pub fn remove_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.remove(&ip);
+    }
+
+    pub fn trace(&self) -> &[Trace<I>] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    pub fn output(&self) -> &[I::Output] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<I::Output> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Reset the instruction pointer, step counter, trace and output, keeping
+    /// the program and breakpoints.
+    pub fn reset(&mut self, regs: I::Regs) {
+        self.regs = regs;
+        self.ip = 0;
+        self.steps = 0;
+        self.output.clear();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
+    }
+
+    /// Execute a single instruction. Returns `None` while the machine can keep
+    /// running.
+    pub fn step(&mut self) -> Option<Exit> {
+        let Some(instr) = self.program.get(self.ip) else {
+            return Some(Exit::Halted);
+        };
+        if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            return Some(Exit::StepLimit);
+        }
+
+        let ip = self.ip;
+        let step = self.isa.execute(instr, &mut self.regs);
+        self.steps += 1;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(Trace {
+                ip,
+                instr: instr.clone(),
+                regs: self.regs.clone(),
+            });
+        }
+
+        match step {
+            Step::Next => self.ip += I::WIDTH,
+            Step::Jump(target) => self.ip = target,
+            Step::Output(value) => {
+                self.output.push(value);
+                self.ip += I::WIDTH;
+                return Some(Exit::Output);
+            }
+            Step::Halt => {
+                self.ip = self.program.len();
+                return Some(Exit::Halted);
+            }
+            Step::Fault => return Some(Exit::Fault(ip)),
+        }
+
+        None
+    }
+
+    /// Run until the program halts, a breakpoint or the step limit is hit.
+    /// A breakpoint on the current instruction is stepped over so that
+    /// running again resumes execution.
+    pub fn run(&mut self) -> Exit {
+        self.run_while(|exit| exit != Exit::Output)
+    }
+
+    /// Like `run`, also stopping after the next output.
+    pub fn run_until_output(&mut self) -> Exit {
+        self.run_while(|_| true)
+    }
+
+    fn run_while(&mut self, mut stop: impl FnMut(Exit) -> bool) -> Exit {
+        let mut first = true;
+
+        loop {
+            if !first && self.breakpoints.contains(&self.ip) {
+                return Exit::Breakpoint(self.ip);
+            }
+            first = false;
+
+            if let Some(exit) = self.step()
+                && stop(exit)
+            {
+                return exit;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Instr {
+        Inc,
+        Dec,
+        Out,
+        Jnz(usize),
+    }
+
+    struct Counter;
+
+    impl InstructionSet for Counter {
+        type Instr = Instr;
+        type Regs = i32;
+        type Output = i32;
+
+        fn execute(&self, instr: &Instr, regs: &mut i32) -> Step<i32> {
+            match *instr {
+                Instr::Inc => *regs += 1,
+                Instr::Dec => *regs -= 1,
+                Instr::Out => return Step::Output(*regs),
+                Instr::Jnz(target) if *regs != 0 => return Step::Jump(target),
+                Instr::Jnz(_) => {}
+            }
+            Step::Next
+        }
+    }
+
+    fn countdown(start: i32) -> Machine<Counter> {
+        // out; dec; jnz 0; inc; out
+        let program = vec![
+            Instr::Out,
+            Instr::Dec,
+            Instr::Jnz(0),
+            Instr::Inc,
+            Instr::Out,
+        ];
+        Machine::new(Counter, program, start)
+    }
+
+    #[test]
+    fn test_run() {
+        let mut machine = countdown(3);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine.output(), &[3, 2, 1, 1]);
+        assert_eq!(machine.steps, 11);
+
+        machine.reset(2);
+        assert_eq!(machine.run_until_output(), Exit::Output);
+        assert_eq!(machine.take_output(), vec![2]);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine.output(), &[1, 1]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let mut machine = countdown(100).with_step_limit(10);
+        assert_eq!(machine.run(), Exit::StepLimit);
+        assert_eq!(machine.steps, 10);
+        assert_eq!(machine.output(), &[100, 99, 98, 97]);
+    }
+
+    #[test]
+    fn test_breakpoint_and_trace() {
+        let mut machine = countdown(2).with_trace();
+        machine.add_breakpoint(3);
+        assert_eq!(machine.run(), Exit::Breakpoint(3));
+        assert_eq!(machine.regs, 0);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine.output(), &[2, 1, 1]);
+
+        let ips = machine.trace().iter().map(|t| t.ip).collect::<Vec<_>>();
+        assert_eq!(ips, vec![0, 1, 2, 0, 1, 2, 3, 4]);
+        assert_eq!(machine.trace()[3].regs, 1);
+    }
+}